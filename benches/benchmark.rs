@@ -12,7 +12,7 @@ pub fn regex_engine_bench(c: &mut Criterion) {
         b.iter(|| Regex::new(black_box(PATTERN)))
     });
 
-    let r = Regex::new(PATTERN);
+    let r = Regex::new(PATTERN).unwrap();
 
     c.bench_function("regex_engine check", |b| {
         b.iter(|| r.check(black_box(TEXT)))
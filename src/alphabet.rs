@@ -0,0 +1,91 @@
+use crate::graph::{Graph, Step};
+use crate::look::Look;
+use crate::unicode::{is_ascii_word_char, is_word_char, perl_class};
+use crate::value::{Value, boundaries};
+
+#[derive(Debug)]
+pub struct Alphabet {
+    // Runs of chars that no transition or assertion of a graph tells apart.
+    //
+    // Class `i` starts at `starts[i]`, and that char stands for the whole run.
+    starts: Vec<char>,
+
+    // Assertions of the graph, deciding what matters about the previous char
+    looks: Look,
+}
+
+impl Alphabet {
+    pub fn new(graph: &Graph<Step<Value>>) -> Self {
+        let mut starts = vec!['\0'];
+        let mut looks = Look::default();
+
+        // Counter states only reuse stored edges and their labels, so those
+        // are all the steps there are
+        let edges = graph
+            .nodes
+            .iter()
+            .flat_map(|x| x.edges.iter().map(|(x, _)| x));
+
+        for step in edges.chain(graph.repeats.iter().map(|x| &x.label)) {
+            looks = looks | step.look;
+
+            if let Some(value) = &step.value {
+                starts.extend(value.boundaries());
+            }
+        }
+
+        // Assertions look at the chars either side too
+        if looks.intersects(Look::START_LINE | Look::END_LINE) {
+            starts.extend(['\n', '\u{B}']);
+        }
+
+        if looks.intersects(Look::WORD | Look::NOT_WORD) {
+            starts.extend(boundaries(perl_class('w', true).unwrap()));
+        }
+
+        if looks.intersects(Look::WORD_ASCII | Look::NOT_WORD_ASCII) {
+            starts.extend(boundaries(perl_class('w', false).unwrap()));
+        }
+
+        starts.sort_unstable();
+        starts.dedup();
+
+        Self { starts, looks }
+    }
+
+    pub fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    pub fn class(&self, c: char) -> usize {
+        // Class of `c`, the last run starting at or before it
+        self.starts.partition_point(|&x| x <= c) - 1
+    }
+
+    pub fn representative(&self, class: usize) -> char {
+        self.starts[class]
+    }
+
+    pub fn prev(&self, c: Option<char>) -> Option<char> {
+        // Char with the same effect as `c` on the assertions when it comes first,
+        // so states only differ by the previous char when it matters
+        if self.looks.is_empty() {
+            return None;
+        }
+
+        let c = c?;
+
+        let looks = self.looks;
+
+        Some(if looks.intersects(Look::START_LINE) && c == '\n' {
+            '\n'
+        } else if looks.intersects(Look::WORD_ASCII | Look::NOT_WORD_ASCII) && is_ascii_word_char(c)
+        {
+            'a'
+        } else if looks.intersects(Look::WORD | Look::NOT_WORD) && is_word_char(c) {
+            'é'
+        } else {
+            ' '
+        })
+    }
+}
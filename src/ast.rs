@@ -1,10 +1,24 @@
 use std::fmt::Display;
 
+use crate::look::Look;
 use crate::value::Value;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum UnOp {
     Range(u32, Option<u32>),
+
+    // Same bounds as `Range`, preferring fewer iterations
+    Lazy(u32, Option<u32>),
+}
+
+impl UnOp {
+    pub fn bounds(&self) -> (u32, Option<u32>, bool) {
+        // Lower and upper bound, and whether more iterations are preferred
+        match *self {
+            UnOp::Range(lower, upper) => (lower, upper, true),
+            UnOp::Lazy(lower, upper) => (lower, upper, false),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -16,22 +30,87 @@ pub enum BinOp {
 #[derive(Debug, PartialEq, Eq)]
 pub enum Ast {
     Sym(Value),
+    Look(Look),
+    Group(usize, Option<String>, Box<Ast>),
     Unary(UnOp, Box<Ast>),
     Binary(BinOp, Box<Ast>, Box<Ast>),
 }
 
+impl Ast {
+    pub fn operands(&self) -> Vec<&Ast> {
+        // Operands of a run of one binary operator in order, `a|b|c` giving `a`, `b` and `c`.
+        // The parser nests runs to the left, as deep as the run is long,
+        // so they are walked in a loop rather than by recursion.
+        let Ast::Binary(op, ..) = self else {
+            return vec![self];
+        };
+
+        let mut out = Vec::new();
+        let mut tree = self;
+
+        while let Ast::Binary(x, t, u) = tree
+            && x == op
+        {
+            out.push(&**u);
+            tree = t;
+        }
+
+        out.push(tree);
+        out.reverse();
+        out
+    }
+}
+
+impl Drop for Ast {
+    fn drop(&mut self) {
+        // Drops nested trees one at a time, long runs nesting too deep to recurse
+        fn take(tree: &mut Ast, stack: &mut Vec<Ast>) {
+            let mut take = |t: &mut Box<Ast>| {
+                stack.push(std::mem::replace(&mut **t, Ast::Look(Look::default())));
+            };
+
+            match tree {
+                Ast::Sym(_) | Ast::Look(_) => (),
+                Ast::Group(_, _, t) | Ast::Unary(_, t) => take(t),
+                Ast::Binary(_, t, u) => {
+                    take(t);
+                    take(u);
+                }
+            }
+        }
+
+        let mut stack = Vec::new();
+        take(self, &mut stack);
+
+        while let Some(mut tree) = stack.pop() {
+            take(&mut tree, &mut stack);
+        }
+    }
+}
+
 impl Display for Ast {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         use Ast::*;
 
         match self {
             Sym(x) => write!(f, "{x}"),
+            Look(x) => write!(f, "{x}"),
+            Group(idx, None, x) => write!(f, "Group{idx}({x})"),
+            Group(idx, Some(name), x) => write!(f, "Group{idx}<{name}>({x})"),
             Unary(op, x) => write!(f, "{op:?}({x})"),
             Binary(op, x, y) => write!(f, "{op:?}({x}, {y})"),
         }
     }
 }
 
+pub fn group(index: usize, ast: Ast) -> Ast {
+    Ast::Group(index, None, Box::new(ast))
+}
+
+pub fn named_group(index: usize, name: &str, ast: Ast) -> Ast {
+    Ast::Group(index, Some(name.to_string()), Box::new(ast))
+}
+
 pub fn unary(op: UnOp, ast: Ast) -> Ast {
     Ast::Unary(op, Box::new(ast))
 }
@@ -0,0 +1,104 @@
+use crate::bytes;
+use crate::compiler::DEFAULT_SIZE_LIMIT;
+use crate::error::RegexError;
+use crate::find::MatchKind;
+use crate::flags::Flags;
+use crate::parser::DEFAULT_NEST_LIMIT;
+use crate::regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct RegexBuilder {
+    pattern: String,
+
+    // Flags in effect from the start of the pattern
+    flags: Flags,
+
+    // Groups and classes allowed inside each other
+    nest_limit: u32,
+
+    // Nodes allowed in the compiled NFA
+    size_limit: usize,
+
+    match_kind: MatchKind,
+}
+
+impl RegexBuilder {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            flags: Flags::UNICODE,
+            nest_limit: DEFAULT_NEST_LIMIT,
+            size_limit: DEFAULT_SIZE_LIMIT,
+            match_kind: MatchKind::default(),
+        }
+    }
+
+    pub fn case_insensitive(&mut self, yes: bool) -> &mut Self {
+        // Same as starting the pattern with `(?i)`
+        self.flags.set(Flags::CASE_INSENSITIVE, yes);
+        self
+    }
+
+    pub fn multi_line(&mut self, yes: bool) -> &mut Self {
+        // Same as starting the pattern with `(?m)`
+        self.flags.set(Flags::MULTI_LINE, yes);
+        self
+    }
+
+    pub fn dot_matches_new_line(&mut self, yes: bool) -> &mut Self {
+        // Same as starting the pattern with `(?s)`
+        self.flags.set(Flags::DOT_ALL, yes);
+        self
+    }
+
+    pub fn ignore_whitespace(&mut self, yes: bool) -> &mut Self {
+        // Same as starting the pattern with `(?x)`
+        self.flags.set(Flags::IGNORE_WHITESPACE, yes);
+        self
+    }
+
+    pub fn unicode(&mut self, yes: bool) -> &mut Self {
+        // On by default, off is the same as starting the pattern with `(?-u)`
+        self.flags.set(Flags::UNICODE, yes);
+        self
+    }
+
+    pub fn nest_limit(&mut self, limit: u32) -> &mut Self {
+        // Deeper patterns fail to parse, rather than overflowing the stack
+        self.nest_limit = limit;
+        self
+    }
+
+    pub fn size_limit(&mut self, limit: usize) -> &mut Self {
+        // Larger patterns fail to compile, rather than exhausting memory
+        self.size_limit = limit;
+        self
+    }
+
+    pub fn match_kind(&mut self, kind: MatchKind) -> &mut Self {
+        // Which of the matches at the leftmost start wins, leftmost-first by default
+        self.match_kind = kind;
+        self
+    }
+
+    pub fn build(&self) -> Result<Regex, RegexError> {
+        Regex::with_options(
+            &self.pattern,
+            self.flags,
+            self.nest_limit,
+            self.size_limit,
+            self.match_kind,
+        )
+    }
+
+    pub fn build_bytes(&self) -> Result<bytes::Regex, RegexError> {
+        // Regex over `&[u8]`. With `unicode(false)` chars up to `\xFF` are raw bytes.
+        bytes::Regex::with_options(
+            &self.pattern,
+            self.flags,
+            self.nest_limit,
+            self.size_limit,
+            self.match_kind,
+        )
+    }
+}
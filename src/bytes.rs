@@ -0,0 +1,229 @@
+use std::fmt::Display;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::builder::RegexBuilder;
+use crate::compiler::{capture_names, compile};
+use crate::error::RegexError;
+use crate::find::MatchKind;
+use crate::flags::Flags;
+use crate::lexer::lexer;
+use crate::nfa::{Bytes, Nfa};
+use crate::parser::parse;
+use crate::token::{Span, TokenKind};
+
+#[derive(Debug)]
+pub struct Regex {
+    // Matches bytes, which needn't be valid UTF-8.
+    //
+    // Chars are matched by their UTF-8 bytes, and assertions look at the chars
+    // either side, with invalid bytes as U+FFFD. Outside Unicode mode, `\xNN`
+    // escapes and classes take chars up to `\xFF` as single raw bytes instead,
+    // so `(?-u:\xFF)` is the byte 0xFF and `(?s-u:.)` any byte. Matches start and
+    // assertions hold only between chars, never inside the UTF-8 of one, unless
+    // Unicode mode is off somewhere in the pattern, which allows every byte.
+    pattern: String,
+    nfa: Nfa,
+    names: Arc<[Option<String>]>,
+
+    // Unicode mode is off somewhere, see `Bytes`
+    raw: bool,
+}
+
+impl Display for Regex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.nfa)
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        RegexBuilder::new(pattern).build_bytes()
+    }
+
+    pub(crate) fn with_options(
+        pattern: &str,
+        flags: Flags,
+        nest_limit: u32,
+        size_limit: usize,
+        kind: MatchKind,
+    ) -> Result<Self, RegexError> {
+        let tokens = lexer(pattern, flags).map_err(|e| e.with_pattern(pattern))?;
+        let ast = parse(&tokens, flags, nest_limit).map_err(|e| e.with_pattern(pattern))?;
+
+        let nfa = compile(&ast, size_limit, kind)
+            .and_then(|x| x.to_byte_nfa(size_limit))
+            .map_err(|kind| {
+                RegexError::new(kind, Span::new(0, pattern.len())).with_pattern(pattern)
+            })?;
+
+        let raw = !flags.contains(Flags::UNICODE)
+            || tokens.iter().any(|x| match &x.kind {
+                TokenKind::NonCapture(x) | TokenKind::SetFlags(x) => x.off.contains(Flags::UNICODE),
+                _ => false,
+            });
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            nfa,
+            names: capture_names(&ast).into(),
+            raw,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // The pattern this was built from
+        &self.pattern
+    }
+
+    pub fn check(&self, text: &[u8]) -> bool {
+        // Checks the text is a match.
+        self.nfa.check_nfa(self.input(text))
+    }
+
+    pub fn has_match(&self, text: &[u8]) -> bool {
+        // Checks whether text contains a match.
+        self.nfa.has_match_nfa(self.input(text))
+    }
+
+    pub fn search<'t>(&self, text: &'t [u8]) -> Option<Match<'t>> {
+        // Finds the leftmost match, the same one as `crate::Regex::search` on valid UTF-8.
+        let (start, end) = self.nfa.search_nfa(self.input(text), 0, text.len())?;
+        Some(Match::new(text, start, end))
+    }
+
+    pub fn find_iter<'r, 't>(&'r self, text: &'t [u8]) -> FindIter<'r, 't> {
+        // Finds successive non-overlapping matches, empty matches move forward by one byte.
+        FindIter {
+            nfa: &self.nfa,
+            input: self.input(text),
+            next: 0,
+            last: None,
+        }
+    }
+
+    pub fn captures<'t>(&self, text: &'t [u8]) -> Option<Captures<'t>> {
+        // Finds the same match as `search`, with the match of every group.
+        let slots = self.nfa.captures(self.input(text), 0)?;
+
+        Some(Captures {
+            text,
+            slots,
+            names: self.names.clone(),
+        })
+    }
+
+    pub fn capture_names(&self) -> impl ExactSizeIterator<Item = Option<&str>> {
+        // Name of each group by index, starting with the unnamed whole match.
+        self.names.iter().map(|x| x.as_deref())
+    }
+
+    fn input<'t>(&self, text: &'t [u8]) -> Bytes<'t> {
+        Bytes {
+            text,
+            raw: self.raw,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'t> {
+    text: &'t [u8],
+    start: usize,
+    end: usize,
+}
+
+impl<'t> Match<'t> {
+    fn new(text: &'t [u8], start: usize, end: usize) -> Self {
+        Self { text, start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn as_bytes(&self) -> &'t [u8] {
+        &self.text[self.range()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+pub struct FindIter<'r, 't> {
+    nfa: &'r Nfa,
+    input: Bytes<'t>,
+
+    // Byte offset to search from
+    next: usize,
+
+    // End of the previous match
+    last: Option<usize>,
+}
+
+impl<'t> Iterator for FindIter<'_, 't> {
+    type Item = Match<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.input.text;
+
+        while self.next <= text.len() {
+            let (start, end) = self.nfa.search_nfa(self.input, self.next, text.len())?;
+
+            self.next = if start == end { end + 1 } else { end };
+
+            // Skip empty matches right after the previous match
+            if start == end && self.last == Some(end) {
+                continue;
+            }
+
+            self.last = Some(end);
+            return Some(Match::new(text, start, end));
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'t> {
+    text: &'t [u8],
+
+    // Start and end of each group, group 0 is the whole match
+    slots: Vec<Option<usize>>,
+    names: Arc<[Option<String>]>,
+}
+
+impl<'t> Captures<'t> {
+    pub fn get(&self, index: usize) -> Option<Match<'t>> {
+        // Match of a group, if it participated in the match.
+        let start = (*self.slots.get(2 * index)?)?;
+        let end = (*self.slots.get(2 * index + 1)?)?;
+
+        Some(Match::new(self.text, start, end))
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Option<Match<'t>>> + '_ {
+        // Matches of every group in order, including unmatched groups.
+        (0..self.slots.len() / 2).map(|x| self.get(x))
+    }
+
+    pub fn name(&self, name: &str) -> Option<Match<'t>> {
+        // Span of a named group, if it participated in the match.
+        let index = self.names.iter().position(|x| x.as_deref() == Some(name))?;
+        self.get(index)
+    }
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use crate::find::{FindIter, Match};
+use crate::nfa::Nfa;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'t> {
+    text: &'t str,
+
+    // Start and end of each group, group 0 is the whole match
+    slots: Vec<Option<usize>>,
+    names: Arc<[Option<String>]>,
+}
+
+impl<'t> Captures<'t> {
+    pub(crate) fn new(
+        text: &'t str,
+        slots: Vec<Option<usize>>,
+        names: Arc<[Option<String>]>,
+    ) -> Self {
+        Self { text, slots, names }
+    }
+
+    pub fn get(&self, index: usize) -> Option<Match<'t>> {
+        // Match of a group, if it participated in the match.
+        let start = (*self.slots.get(2 * index)?)?;
+        let end = (*self.slots.get(2 * index + 1)?)?;
+
+        Some(Match::new(self.text, start, end))
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Option<Match<'t>>> + '_ {
+        // Matches of every group in order, including unmatched groups.
+        (0..self.slots.len() / 2).map(|x| self.get(x))
+    }
+
+    pub fn name(&self, name: &str) -> Option<Match<'t>> {
+        // Span of a named group, if it participated in the match.
+        let index = self.names.iter().position(|x| x.as_deref() == Some(name))?;
+        self.get(index)
+    }
+
+    pub fn expand(&self, template: &str, dst: &mut String) {
+        // Appends `template` with `$1` or `$name` replaced by the group's text, `$$` by `$`.
+        // Names are as long as possible, so `${1}a` separates a group from the text after it.
+        // Missing or unmatched groups are empty, and a `$` starting no reference is kept.
+        let mut rest = template;
+
+        while let Some(index) = rest.find('$') {
+            dst.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            if let Some(after) = rest.strip_prefix('$') {
+                dst.push('$');
+                rest = after;
+                continue;
+            }
+
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(inner) => match inner.find('}') {
+                    Some(close) => (&inner[..close], &inner[close + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let len = rest
+                        .find(|c: char| c != '_' && !c.is_ascii_alphanumeric())
+                        .unwrap_or(rest.len());
+
+                    (&rest[..len], &rest[len..])
+                }
+            };
+
+            if name.is_empty() {
+                dst.push('$');
+                continue;
+            }
+
+            let group = match name.parse::<usize>() {
+                Ok(index) => self.get(index),
+                Err(_) => self.name(name),
+            };
+
+            dst.push_str(group.map_or("", |x| x.as_str()));
+            rest = after;
+        }
+
+        dst.push_str(rest);
+    }
+}
+
+pub struct CapturesIter<'r, 't> {
+    // Captures of each match found by `find_iter`
+    matches: FindIter<'r, 't>,
+    nfa: &'r Nfa,
+    text: &'t str,
+    names: Arc<[Option<String>]>,
+}
+
+impl<'r, 't> CapturesIter<'r, 't> {
+    pub(crate) fn new(nfa: &'r Nfa, text: &'t str, names: Arc<[Option<String>]>) -> Self {
+        Self {
+            matches: FindIter::new(nfa, text),
+            nfa,
+            text,
+            names,
+        }
+    }
+}
+
+impl<'t> Iterator for CapturesIter<'_, 't> {
+    type Item = Captures<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Starting at the match finds the same match again
+        let m = self.matches.next()?;
+        let slots = self.nfa.captures(self.text, m.start())?;
+
+        Some(Captures::new(self.text, slots, self.names.clone()))
+    }
+}
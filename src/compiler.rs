@@ -1,103 +1,234 @@
-use crate::ast::{Ast, BinOp, UnOp};
-use crate::graph::{Graph, Node};
+use std::collections::BTreeSet;
+
+use crate::ast::{Ast, BinOp};
+use crate::error::ErrorKind;
+use crate::find::MatchKind;
+use crate::graph::{Graph, Label, Node, Repeat, Step};
+use crate::look::Look;
 use crate::nfa::Nfa;
 use crate::set::Set as State;
 use crate::value::Value;
 
-fn build(tree: &Ast, graph: &mut Graph<Option<Value>>) -> (usize, usize) {
+// Nodes allowed in the epsilon NFA, checked against an estimate before building,
+// and the work allowed to remove its epsilons
+pub const DEFAULT_SIZE_LIMIT: usize = 100_000;
+
+fn is_counted(tree: &Ast, lower: u32, upper: Option<u32>) -> bool {
+    // Repeats of a single value are built as counter states, see `Repeat`
+    matches!(tree, Ast::Sym(_)) && upper.unwrap_or(lower) > 1
+}
+
+fn estimate(tree: &Ast) -> usize {
+    // Number of nodes `build` creates, saturating instead of overflowing
+    use Ast::*;
+
+    match tree {
+        Sym(_) | Look(_) => 2,
+        Group(_, _, t) => estimate(t).saturating_add(2),
+        Unary(op, t) => {
+            let (lower, upper, _) = op.bounds();
+
+            if is_counted(t, lower, upper) {
+                return upper.unwrap_or(lower) as usize + 2;
+            }
+
+            let copies = upper.unwrap_or(lower.max(1));
+            estimate(t)
+                .saturating_mul(copies as usize)
+                .saturating_add(2)
+        }
+        Binary(op, ..) => {
+            let ends = if *op == BinOp::Union { 2 } else { 0 };
+
+            tree.operands()
+                .into_iter()
+                .fold(ends, |n, t| n.saturating_add(estimate(t)))
+        }
+    }
+}
+
+fn nullable(tree: &Ast) -> bool {
+    // Whether the tree can match without consuming anything
+    use Ast::*;
+
+    match tree {
+        Sym(_) => false,
+        Look(_) => true,
+        Group(_, _, t) => nullable(t),
+        Unary(op, t) => op.bounds().0 == 0 || nullable(t),
+        Binary(BinOp::Union, ..) => tree.operands().into_iter().any(nullable),
+        Binary(BinOp::Concat, ..) => tree.operands().into_iter().all(nullable),
+    }
+}
+
+fn add_choice(
+    graph: &mut Graph<Label<Value>>,
+    node: usize,
+    repeat: usize,
+    leave: usize,
+    greedy: bool,
+) {
+    // Greedy quantifiers prefer another iteration over leaving, lazy ones the reverse
+    let (first, second) = if greedy {
+        (repeat, leave)
+    } else {
+        (leave, repeat)
+    };
+
+    graph.add_e(node, first);
+    graph.add_e(node, second);
+}
+
+// Edges leaving a node are ordered by priority, highest first.
+fn build(tree: &Ast, graph: &mut Graph<Label<Value>>) -> (usize, usize) {
     use Ast::*;
     use BinOp::*;
-    use UnOp::*;
 
     match tree {
         Sym(x) => {
             let start = graph.new_node();
             let end = graph.new_node();
 
-            graph.add_edge(start, end, Some(x.clone()));
+            graph.add_edge(start, end, Label::Value(x.clone()));
 
             (start, end)
         }
 
-        Unary(op, t) => match op {
-            Range(lower, upper) => {
-                let start = graph.new_node();
-                let mut cur = start;
-                let mut prev = None;
+        Look(x) => {
+            let start = graph.new_node();
+            let end = graph.new_node();
 
-                // Build chain of NFA `lower` times
-                for _ in 0..*lower {
-                    let nfa = build(t, graph);
-                    graph.add_e(cur, nfa.0);
-                    prev = Some(cur);
-                    cur = nfa.1;
+            graph.add_look(start, end, *x);
+
+            (start, end)
+        }
+
+        Group(index, _, t) => {
+            let start = graph.new_node();
+            let nfa = build(t, graph);
+            let end = graph.new_node();
+
+            graph.add_save(start, nfa.0, 2 * index);
+            graph.add_save(nfa.1, end, 2 * index + 1);
+
+            (start, end)
+        }
+
+        Unary(op, t) => {
+            let (lower, upper, greedy) = op.bounds();
+
+            if let Sym(x) = &**t
+                && is_counted(t, lower, upper)
+            {
+                let lower = lower as usize;
+                let upper = upper.map(|x| x as usize);
+
+                let base = graph.len();
+
+                for _ in 0..=upper.unwrap_or(lower) {
+                    graph.new_node();
                 }
 
-                match upper {
-                    Some(upper) => {
-                        let end = graph.new_node();
-                        graph.add_e(cur, end);
+                let end = graph.new_node();
 
-                        // Doesn't do anything extra if upper < lower
-                        // Should be validated in parser anyway
-                        for _ in *lower..*upper {
-                            let nfa = build(t, graph);
-                            graph.add_e(cur, nfa.0);
-                            cur = nfa.1;
+                graph.add_repeat(Repeat {
+                    base,
+                    lower,
+                    upper,
+                    greedy,
+                    label: Label::Value(x.clone()),
+                    exit: end,
+                });
 
-                            // Optimised to jump to end if fail
-                            graph.add_e(cur, end);
-                        }
+                return (base, end);
+            }
 
-                        (start, end)
-                    }
+            let start = graph.new_node();
+            let mut cur = start;
+            let mut prev = None;
+
+            // Build chain of NFA `lower` times
+            for _ in 0..lower {
+                let nfa = build(t, graph);
+                graph.add_e(cur, nfa.0);
+                prev = Some(cur);
+                cur = nfa.1;
+            }
 
-                    None if prev.is_some() => {
-                        graph.add_e(cur, prev.unwrap());
-                        (start, cur)
-                    }
+            let end = graph.new_node();
 
-                    // {0,} Special case
-                    None => {
+            match (upper, prev) {
+                (Some(upper), _) => {
+                    // Doesn't do anything extra if upper < lower
+                    // Should be validated in parser anyway
+                    for _ in lower..upper {
                         let nfa = build(t, graph);
-                        graph.add_e(start, nfa.0);
-                        graph.add_e(nfa.1, start);
 
-                        (start, start)
+                        // Optimised to jump to end if fail
+                        add_choice(graph, cur, nfa.0, end, greedy);
+                        cur = nfa.1;
                     }
+
+                    graph.add_e(cur, end);
+                }
+
+                // Repeats the last copy
+                (None, Some(prev)) => add_choice(graph, cur, prev, end, greedy),
+
+                // {0,} of something that can match empty, built as `(?:e+)?`.
+                // An iteration that matched nothing can't loop back to the start,
+                // so leaving has its priority rather than the lowest
+                (None, None) if nullable(t) => {
+                    let nfa = build(t, graph);
+                    add_choice(graph, start, nfa.0, end, greedy);
+                    add_choice(graph, nfa.1, nfa.0, end, greedy);
+                }
+
+                // {0,} Special case
+                (None, None) => {
+                    let nfa = build(t, graph);
+                    add_choice(graph, start, nfa.0, end, greedy);
+                    graph.add_e(nfa.1, start);
                 }
             }
-        },
 
-        Binary(op, t, u) => {
-            let nfa = build(t, graph);
-            let nfa2 = build(u, graph);
+            (start, end)
+        }
+
+        Binary(op, ..) => {
+            // A whole run of the operator at once, see `Ast::operands`
+            let nfas: Vec<_> = tree
+                .operands()
+                .into_iter()
+                .map(|t| build(t, graph))
+                .collect();
 
             match op {
                 Union => {
                     let start = graph.new_node();
                     let end = graph.new_node();
 
-                    graph.add_e(start, nfa.0);
-                    graph.add_e(start, nfa2.0);
-
-                    graph.add_e(nfa.1, end);
-                    graph.add_e(nfa2.1, end);
+                    for nfa in nfas {
+                        graph.add_e(start, nfa.0);
+                        graph.add_e(nfa.1, end);
+                    }
 
                     (start, end)
                 }
 
                 Concat => {
-                    graph.add_e(nfa.1, nfa2.0);
+                    for x in nfas.windows(2) {
+                        graph.add_e(x[0].1, x[1].0);
+                    }
 
-                    (nfa.0, nfa2.1)
+                    (nfas[0].0, nfas[nfas.len() - 1].1)
                 }
             }
         }
     }
 }
 
-fn compactify_forward<T>(graph: &mut Graph<Option<T>>, end: usize) {
+fn compactify_forward<T>(graph: &mut Graph<Label<T>>, end: usize) {
     // Skip destination along epsilon chains.
     //
     // Avoids managing more states and reduce epsilon depth.
@@ -106,26 +237,35 @@ fn compactify_forward<T>(graph: &mut Graph<Option<T>>, end: usize) {
     // Don't skip past end state
     cache[end] = Some(end);
 
-    fn only_epsilon<T>(e: &[(Option<T>, usize)]) -> bool {
-        e.len() == 1 && e[0].0.is_none()
+    fn only_epsilon<T>(e: &[(Label<T>, usize)]) -> bool {
+        e.len() == 1 && matches!(e[0].0, Label::Epsilon)
     }
 
-    fn skip<T>(cache: &mut Vec<Option<usize>>, graph: &Graph<Option<T>>, node: usize) -> usize {
-        if let Some(x) = cache[node] {
-            x
-        } else {
-            let e = &graph.nodes[node].edges;
+    fn skip<T>(cache: &mut [Option<usize>], graph: &Graph<Label<T>>, node: usize) -> usize {
+        // Follows the chain in a loop, as chains can be as long as the pattern
+        let mut chain = vec![];
+        let mut cur = node;
+
+        let res = loop {
+            if let Some(x) = cache[cur] {
+                break x;
+            }
+
+            let e = &graph.nodes[cur].edges;
 
-            let res = if only_epsilon(e) {
-                skip(cache, graph, e[0].1)
-            } else {
-                node
-            };
+            if !only_epsilon(e) {
+                break cur;
+            }
 
-            cache[node] = Some(res);
+            chain.push(cur);
+            cur = e[0].1;
+        };
 
-            res
+        for x in chain.into_iter().chain([cur]) {
+            cache[x] = Some(res);
         }
+
+        res
     }
 
     // Can't use map because graph needs to be accessible for `skip`
@@ -138,54 +278,228 @@ fn compactify_forward<T>(graph: &mut Graph<Option<T>>, end: usize) {
 }
 
 fn compactify_backward<T: Clone>(
-    graph: &mut Graph<Option<T>>,
+    graph: &Graph<Label<T>>,
+    start: usize,
     end: usize,
-) -> (Graph<T>, Vec<usize>) {
+    limit: usize,
+) -> Result<Graph<Step<T>>, ErrorKind> {
     // Converts NFA-e to just NFA
     // Copies non-epsilon transitions in epsilon chains and removes epsilon.
+    // Saves passed along the chain are kept on the copied transition.
+    //
+    // Nodes passed and transitions copied are charged against `limit`,
+    // as the copies can grow with the square of the number of nodes.
     //
     // NOTE: It may not be beneficial to remove epsilons entirely
     // due to edges becoming more duplicated across states.
     // However, this is usually more performant (more benchmarks needed)
     let mut state = State::new(graph.len());
+    let mut looks = vec![vec![]; graph.len()];
     let mut out = Graph::default();
+    let mut left = limit;
 
-    let mut ends = vec![];
+    // Only nodes entered by consuming need their own transitions,
+    // the rest are passed over in the closures of those nodes
+    let mut needed = vec![false; graph.len()];
+    needed[start] = true;
 
-    for node in 0..graph.len() {
-        graph.traverse(node, &mut state);
+    for node in &graph.nodes {
+        for (label, next) in &node.edges {
+            if let Label::Value(_) = label {
+                needed[*next] = true;
+            }
+        }
+    }
+
+    for repeat in &graph.repeats {
+        needed[repeat.exit] = true;
+    }
+
+    struct Closure<'a, T> {
+        graph: &'a Graph<Label<T>>,
+        end: usize,
+        seen: &'a mut State,
 
-        if state.contains(end) {
-            ends.push(node);
+        // Assertions of each path that has reached a node
+        looks: &'a mut [Vec<Look>],
+        saves: Vec<usize>,
+        look: Look,
+        edges: Vec<(Step<T>, usize)>,
+
+        // Work allowed before giving up
+        left: &'a mut usize,
+        exceeded: bool,
+    }
+
+    impl<T: Clone> Closure<'_, T> {
+        fn charge(&mut self) -> bool {
+            match self.left.checked_sub(1) {
+                Some(x) => *self.left = x,
+                None => self.exceeded = true,
+            }
+
+            !self.exceeded
         }
 
-        let edges: Vec<(T, usize)> = state
-            .usizes
-            .iter()
-            .flat_map(|x| graph.nodes[*x].edges.iter())
-            .filter_map(|x| x.0.clone().map(|y| (y, x.1)))
-            .collect();
+        fn push(&mut self, value: Option<T>, next: usize) {
+            if self.charge() {
+                self.edges.push((self.step(value), next));
+            }
+        }
+
+        fn step(&self, value: Option<T>) -> Step<T> {
+            Step {
+                value,
+                saves: self.saves.clone(),
+                look: self.look,
+            }
+        }
+
+        fn enter(&mut self, node: usize) -> bool {
+            // Whether the path so far is new to `node`, adding the match if it is the end
+            if self.looks[node].iter().any(|x| self.look.contains(*x)) || !self.charge() {
+                return false;
+            }
+
+            self.seen.insert(node);
+            self.looks[node].push(self.look);
+
+            if node == self.end {
+                self.push(None, node);
+            }
+
+            true
+        }
+
+        fn visit(&mut self, node: usize) {
+            // DFS in priority order, so the first path to a node wins.
+            // A later path with fewer assertions can still pass when the first fails.
+            // Paths can be as long as the pattern, so entered nodes are kept on a stack
+            // with the edges left to follow and the saves and assertions of the path.
+            let graph = self.graph;
+            let mut stack = vec![];
+
+            if self.enter(node) {
+                stack.push((graph.edges(node), self.saves.len(), self.look));
+            }
+
+            while let Some((edges, saves, look)) = stack.last_mut() {
+                self.saves.truncate(*saves);
+                self.look = *look;
+
+                // Fine to iterate as graph is sparse
+                let Some((label, next)) = edges.next() else {
+                    stack.pop();
+                    continue;
+                };
+
+                match label {
+                    Label::Value(x) => {
+                        self.push(Some(x.clone()), next);
+                        continue;
+                    }
+                    Label::Epsilon => (),
+                    Label::Save(slot) => self.saves.push(*slot),
+                    Label::Look(x) => self.look = self.look | *x,
+                }
+
+                if self.enter(next) {
+                    stack.push((graph.edges(next), self.saves.len(), self.look));
+                }
+            }
+        }
+    }
+
+    for (node, &needed) in needed.iter().enumerate() {
+        // Counter states keep computing their edges, from the compacted exit
+        if let Some(repeat) = graph.nodes[node].repeat {
+            out.nodes.push(Node {
+                edges: vec![],
+                repeat: Some(repeat),
+            });
+
+            continue;
+        }
+
+        if !needed {
+            out.nodes.push(Node::default());
+            continue;
+        }
+
+        let mut closure = Closure {
+            graph,
+            end,
+            seen: &mut state,
+            looks: &mut looks,
+            saves: vec![],
+            look: Look::default(),
+            edges: vec![],
+            left: &mut left,
+            exceeded: false,
+        };
+
+        closure.visit(node);
+
+        if closure.exceeded {
+            return Err(ErrorKind::SizeLimitExceeded(limit));
+        }
+
+        out.nodes.push(Node {
+            edges: closure.edges,
+            repeat: None,
+        });
+
+        for &x in &state.usizes {
+            looks[x].clear();
+        }
 
-        out.nodes.push(Node { edges });
         state.clear();
     }
 
-    (out, ends)
+    for repeat in &graph.repeats {
+        let Label::Value(x) = &repeat.label else {
+            unreachable!()
+        };
+
+        out.repeats.push(Repeat {
+            base: repeat.base,
+            lower: repeat.lower,
+            upper: repeat.upper,
+            greedy: repeat.greedy,
+            label: Step {
+                value: Some(x.clone()),
+                saves: vec![],
+                look: Look::default(),
+            },
+            exit: repeat.exit,
+        });
+    }
+
+    Ok(out)
 }
 
-fn compactify<T: Clone>(graph: &mut Graph<Option<T>>, end: usize) -> (Graph<T>, Vec<usize>) {
+fn compactify<T: Clone>(
+    graph: &mut Graph<Label<T>>,
+    start: usize,
+    end: usize,
+    limit: usize,
+) -> Result<Graph<Step<T>>, ErrorKind> {
     // Skips epsilons.
     compactify_forward(graph, end);
-    compactify_backward(graph, end)
+    compactify_backward(graph, start, end, limit)
 }
 
-fn remove_duplicate_edges<T: Ord>(graph: &mut Graph<T>) {
+fn remove_duplicate_edges<T: Ord + Clone>(graph: &mut Graph<Step<T>>) {
+    // Keeps the first of each transition, as it has the highest priority
     for node in &mut graph.nodes {
-        node.edges.sort_unstable();
-        node.edges.dedup();
+        let mut seen = BTreeSet::new();
+        node.edges
+            .retain(|(step, next)| seen.insert((step.value.clone(), step.look, *next)));
     }
 }
 
+// Kept for when memory matters more than build time, see `compile`
+#[allow(dead_code)]
 fn remove_nodes<T: Clone>(
     graph: &Graph<T>,
     start: usize,
@@ -265,15 +579,57 @@ fn remove_nodes<T: Clone>(
         ends.iter().filter_map(|x| map[*x]).collect(),
     )
 }
-pub fn compile(tree: &Ast) -> Nfa {
+pub fn capture_names(tree: &Ast) -> Vec<Option<String>> {
+    // Name of each group by index, group 0 is the whole match
+    fn visit(tree: &Ast, out: &mut Vec<Option<String>>) {
+        match tree {
+            Ast::Sym(_) | Ast::Look(_) => (),
+            Ast::Group(index, name, t) => {
+                if out.len() <= *index {
+                    out.resize(index + 1, None);
+                }
+
+                out[*index] = name.clone();
+                visit(t, out);
+            }
+            Ast::Unary(_, t) => visit(t, out),
+            Ast::Binary(..) => {
+                for t in tree.operands() {
+                    visit(t, out);
+                }
+            }
+        }
+    }
+
+    let mut out = vec![None];
+    visit(tree, &mut out);
+    out
+}
+
+pub fn compile(tree: &Ast, size_limit: usize, kind: MatchKind) -> Result<Nfa, ErrorKind> {
     let mut graph = Graph::default();
-    let (start, end) = build(tree, &mut graph);
-    let (mut graph2, ends) = compactify(&mut graph, end);
+
+    // Group 0 is the whole match
+    let groups = capture_names(tree).len();
+
+    // Checked up front, so huge repetitions fail before allocating anything
+    if estimate(tree).saturating_add(2) > size_limit {
+        return Err(ErrorKind::SizeLimitExceeded(size_limit));
+    }
+
+    let start = graph.new_node();
+    let nfa = build(tree, &mut graph);
+    let end = graph.new_node();
+
+    graph.add_save(start, nfa.0, 0);
+    graph.add_save(nfa.1, end, 1);
+
+    let mut graph2 = compactify(&mut graph, start, end, size_limit)?;
     remove_duplicate_edges(&mut graph2);
 
     // Reduces memory but not much performance
-    // let (new_graph, s, e) = remove_nodes(&graph2, start, &ends);
-    // Nfa::new(new_graph, s, e)
+    // let (new_graph, s, e) = remove_nodes(&graph2, start, &[end]);
+    // Nfa::new(new_graph, s, e[0], 2 * groups)
 
-    Nfa::new(graph2, start, ends)
+    Ok(Nfa::new(graph2, start, end, 2 * groups, kind))
 }
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use crate::error::ErrorKind;
+use crate::graph::{Graph, Step};
+use crate::lazy::LazyDfa;
+use crate::serialize::{DeserializeError, Kind, Reader, Writer};
+use crate::value::Value;
+
+pub const DEFAULT_STATE_LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dfa<T = Vec<u32>> {
+    // Complete minimal DFA, looking up one transition per char.
+    //
+    // Chars are split into classes, class `i` being the run starting at the char `starts[i]`.
+    // Row `s` of `trans` has an entry per class and a last one for the end of the text,
+    // each the next state shifted left, with the low bit set if there is a match before it.
+    // Tables are any slice of words, so a DFA can be used straight from loaded bytes.
+    starts: T,
+    trans: T,
+    anchored: u32,
+    unanchored: u32,
+
+    // State no text leads out of without matching, if any
+    dead: Option<u32>,
+
+    // Class of each ASCII char, saving the search for the most common chars
+    ascii: [u32; 128],
+}
+
+impl Dfa {
+    pub(crate) fn new(
+        graph: &Graph<Step<Value>>,
+        start: usize,
+        limit: usize,
+    ) -> Result<Self, ErrorKind> {
+        let explored =
+            LazyDfa::explore(graph, start, limit).ok_or(ErrorKind::StateLimitExceeded(limit))?;
+
+        let classes = explored.starts.len();
+        let (block, blocks) = minimise(&explored.trans, &explored.ends, classes);
+
+        // Any state of a block has the same transitions, up to blocks
+        let mut trans = vec![0; blocks * (classes + 1)];
+
+        for (state, &b) in block.iter().enumerate() {
+            let row = &mut trans[b * (classes + 1)..(b + 1) * (classes + 1)];
+            let old = &explored.trans[state * classes..(state + 1) * classes];
+
+            for (x, &t) in row.iter_mut().zip(old) {
+                *x = (block[t as usize >> 1] as u32) << 1 | t & 1;
+            }
+
+            row[classes] = (b as u32) << 1 | u32::from(explored.ends[state]);
+        }
+
+        Ok(Self::from_parts(
+            explored.starts.iter().map(|&x| x as u32).collect(),
+            trans,
+            block[explored.anchored] as u32,
+            block[explored.unanchored] as u32,
+            explored.dead.map(|x| block[x] as u32),
+        ))
+    }
+}
+
+impl<'a> Dfa<&'a [u32]> {
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, DeserializeError> {
+        // Loads the output of `to_bytes` without copying the tables.
+        // The bytes must be aligned to 4 and in this machine's byte order.
+        // Corrupt data is an error rather than a DFA that panics.
+        let mut r = Reader::new(bytes, Kind::Dfa)?;
+
+        let classes = r.count(1)?;
+        let states = r.count(classes + 1)?;
+
+        let mut state = || match r.u32()? {
+            x if (x as usize) < states => Ok(Some(x)),
+            u32::MAX => Ok(None),
+            _ => Err(DeserializeError::Invalid("state out of range")),
+        };
+
+        let anchored = state()?;
+        let unanchored = state()?;
+        let dead = state()?;
+
+        let starts = r.words(classes)?;
+        let trans = r.words(states * (classes + 1))?;
+        r.finish()?;
+
+        let sorted = starts.first() == Some(&0)
+            && starts.windows(2).all(|x| x[0] < x[1])
+            && starts.iter().all(|&x| char::from_u32(x).is_some());
+
+        if !sorted {
+            return Err(DeserializeError::Invalid("classes out of order"));
+        }
+
+        if trans.iter().any(|&x| (x >> 1) as usize >= states) {
+            return Err(DeserializeError::Invalid("state out of range"));
+        }
+
+        match (anchored, unanchored) {
+            (Some(x), Some(y)) => Ok(Self::from_parts(starts, trans, x, y, dead)),
+            _ => Err(DeserializeError::Invalid("missing start state")),
+        }
+    }
+
+    pub fn into_owned(self) -> Dfa {
+        Dfa::from_parts(
+            self.starts.to_vec(),
+            self.trans.to_vec(),
+            self.anchored,
+            self.unanchored,
+            self.dead,
+        )
+    }
+}
+
+impl<T: AsRef<[u32]>> Dfa<T> {
+    fn from_parts(starts: T, trans: T, anchored: u32, unanchored: u32, dead: Option<u32>) -> Self {
+        let mut ascii = [0; 128];
+
+        for (x, class) in ascii.iter_mut().enumerate() {
+            *class = (starts.as_ref().partition_point(|&y| y <= x as u32) - 1) as u32;
+        }
+
+        Self {
+            starts,
+            trans,
+            anchored,
+            unanchored,
+            dead,
+            ascii,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Tables in this machine's byte order, for `from_bytes`.
+        let mut w = Writer::new(Kind::Dfa);
+
+        w.usize(self.starts.as_ref().len());
+        w.usize(self.len());
+
+        for state in [Some(self.anchored), Some(self.unanchored), self.dead] {
+            w.u32(state.unwrap_or(u32::MAX));
+        }
+
+        w.words(self.starts.as_ref());
+        w.words(self.trans.as_ref());
+        w.finish()
+    }
+
+    pub fn len(&self) -> usize {
+        // Number of states
+        self.trans.as_ref().len() / (self.starts.as_ref().len() + 1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trans.as_ref().is_empty()
+    }
+
+    fn class(&self, c: char) -> usize {
+        match self.ascii.get(c as usize) {
+            Some(&x) => x as usize,
+            None => self.starts.as_ref().partition_point(|&x| x <= c as u32) - 1,
+        }
+    }
+
+    fn next(&self, state: u32, class: usize) -> u32 {
+        let stride = self.starts.as_ref().len() + 1;
+        self.trans.as_ref()[state as usize * stride + class]
+    }
+
+    fn end(&self) -> usize {
+        // Entry for the end of the text
+        self.starts.as_ref().len()
+    }
+
+    pub fn check(&self, text: &str) -> bool {
+        // Checks the text is a match.
+        let mut state = self.anchored;
+
+        for c in text.chars() {
+            state = self.next(state, self.class(c)) >> 1;
+
+            if Some(state) == self.dead {
+                return false;
+            }
+        }
+
+        self.next(state, self.end()) & 1 == 1
+    }
+
+    pub fn has_match(&self, text: &str) -> bool {
+        // Checks whether text contains a match.
+        self.shortest_match(text).is_some()
+    }
+
+    pub fn shortest_match(&self, text: &str) -> Option<usize> {
+        // Byte offset where the first match to be seen ends,
+        // which may be before the end of the match `search` finds.
+        let mut state = self.unanchored;
+
+        for (index, c) in text.char_indices() {
+            let next = self.next(state, self.class(c));
+
+            if next & 1 == 1 {
+                return Some(index);
+            }
+
+            state = next >> 1;
+        }
+
+        (self.next(state, self.end()) & 1 == 1).then_some(text.len())
+    }
+}
+
+fn minimise(trans: &[u32], ends: &[bool], classes: usize) -> (Vec<usize>, usize) {
+    // Groups equivalent states with Hopcroft's algorithm, returning the group of each state.
+    // States start grouped by whether they match before each class and at the end,
+    // and groups are split until each class leads every state of a group to the same group.
+    let states = ends.len();
+
+    // Sources of the transitions into state `t` on class `c`,
+    // at `preds[offsets[c * states + t]..offsets[c * states + t + 1]]`
+    let mut offsets = vec![0; classes * states + 1];
+
+    for (i, &t) in trans.iter().enumerate() {
+        offsets[i % classes * states + (t >> 1) as usize + 1] += 1;
+    }
+
+    for i in 1..offsets.len() {
+        offsets[i] += offsets[i - 1];
+    }
+
+    let mut preds = vec![0; trans.len()];
+    let mut filled = offsets.clone();
+
+    for (i, &t) in trans.iter().enumerate() {
+        let x = &mut filled[i % classes * states + (t >> 1) as usize];
+        preds[*x] = i / classes;
+        *x += 1;
+    }
+
+    let mut block = vec![0; states];
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    let mut groups = HashMap::new();
+
+    for state in 0..states {
+        let row = &trans[state * classes..(state + 1) * classes];
+        let outputs: Vec<_> = row
+            .iter()
+            .map(|x| x & 1 == 1)
+            .chain([ends[state]])
+            .collect();
+
+        let b = *groups.entry(outputs).or_insert_with(|| {
+            members.push(Vec::new());
+            members.len() - 1
+        });
+
+        block[state] = b;
+        members[b].push(state);
+    }
+
+    let mut queued = vec![true; members.len() * classes];
+    let mut work: Vec<_> = (0..members.len())
+        .flat_map(|b| (0..classes).map(move |c| (b, c)))
+        .collect();
+
+    let mut marked = vec![false; states];
+
+    while let Some((splitter, c)) = work.pop() {
+        queued[splitter * classes + c] = false;
+
+        // States leading into the splitter on `c`, by their block
+        let mut touched: HashMap<usize, Vec<usize>> = HashMap::new();
+
+        for &t in &members[splitter] {
+            for &s in &preds[offsets[c * states + t]..offsets[c * states + t + 1]] {
+                if !marked[s] {
+                    marked[s] = true;
+                    touched.entry(block[s]).or_default().push(s);
+                }
+            }
+        }
+
+        for (b, inside) in touched {
+            let split = inside.len() < members[b].len();
+
+            if split {
+                members[b].retain(|&s| !marked[s]);
+            }
+
+            for &s in &inside {
+                marked[s] = false;
+            }
+
+            if !split {
+                continue;
+            }
+
+            let new = members.len();
+
+            for &s in &inside {
+                block[s] = new;
+            }
+
+            members.push(inside);
+            queued.resize(queued.len() + classes, false);
+
+            // Both halves are needed where the block was still queued, otherwise the smaller
+            let smaller = if members[new].len() < members[b].len() {
+                new
+            } else {
+                b
+            };
+
+            for c in 0..classes {
+                let x = if queued[b * classes + c] {
+                    new
+                } else {
+                    smaller
+                };
+
+                if !queued[x * classes + c] {
+                    queued[x * classes + c] = true;
+                    work.push((x, c));
+                }
+            }
+        }
+    }
+
+    (block, members.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimise_merges_equivalent_states() {
+        // States 1 and 2 both loop to a matching state 3 on the only class
+        let trans = [1 << 1, 3 << 1, 3 << 1, 3 << 1 | 1];
+        let ends = [false, false, false, true];
+
+        let (block, blocks) = minimise(&trans, &ends, 1);
+
+        assert_eq!(blocks, 3);
+        assert_eq!(block[1], block[2]);
+        assert_ne!(block[0], block[1]);
+    }
+
+    #[test]
+    fn minimise_splits_by_successor() {
+        // Only state 1 leads to a match
+        let trans = [0, 2 << 1, 2 << 1 | 1];
+        let ends = [false, false, false];
+
+        let (block, blocks) = minimise(&trans, &ends, 1);
+
+        assert_eq!(blocks, 3);
+        assert_ne!(block[0], block[1]);
+    }
+}
@@ -0,0 +1,181 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Range;
+
+use crate::token::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    // Lexer
+    UnknownEscape(char),
+    IncompleteEscape,
+    ExpectedHex(Option<char>),
+    InvalidCodepoint(u32),
+    IncompleteClass,
+    UnknownGroup(char),
+    InvalidGroupName(String),
+    UnclosedGroupName,
+    UnclosedProperty,
+    UnclosedPosixClass(Option<char>),
+
+    // Parser
+    UnexpectedEnd,
+    MissingExpression(char),
+    NothingToRepeat(char),
+    UnclosedGroup,
+    UnmatchedParen,
+    UnmatchedBracket,
+    UnmatchedBrace,
+    EmptyClass,
+    IncompleteClassRange(Option<char>),
+    InvalidClassRange(char, char),
+    InvalidRepetition(Option<char>),
+    UnclosedRepetition(Option<char>),
+    RepetitionTooLarge,
+    InvalidRepetitionRange(u32, u32),
+    DuplicateGroupName(String),
+    UnknownProperty(String),
+    UnknownPosixClass(String),
+    NestLimitExceeded(u32),
+
+    // Compiler
+    SizeLimitExceeded(usize),
+    StateLimitExceeded(usize),
+}
+
+struct Found(Option<char>);
+
+impl Display for Found {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(c) => write!(f, "`{}`", c.escape_debug()),
+            None => write!(f, "end of pattern"),
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use ErrorKind::*;
+
+        match self {
+            UnknownEscape(c) => write!(f, "unknown escape `\\{}`", c.escape_debug()),
+            IncompleteEscape => write!(f, "expected escaped character, found end of pattern"),
+            ExpectedHex(c) => write!(f, "expected hex digit, found {}", Found(*c)),
+            InvalidCodepoint(x) => write!(f, "invalid codepoint U+{x:04X}"),
+            IncompleteClass => write!(f, "unclosed class opened"),
+            UnknownGroup(c) => write!(f, "unknown group flag {}", Found(Some(*c))),
+            InvalidGroupName(x) => write!(f, "invalid group name `{x}`"),
+            UnclosedGroupName => write!(f, "expected `>` after group name, found end of pattern"),
+            UnclosedProperty => {
+                write!(f, "expected `}}` after property name, found end of pattern")
+            }
+            UnclosedPosixClass(c) => {
+                write!(f, "expected `:]` after class name, found {}", Found(*c))
+            }
+
+            UnexpectedEnd => write!(f, "expected expression, found end of pattern"),
+            MissingExpression(c) => write!(f, "expected expression, found {}", Found(Some(*c))),
+            NothingToRepeat(c) => write!(f, "quantifier `{c}` has nothing to repeat"),
+            UnclosedGroup => write!(f, "unclosed group opened"),
+            UnmatchedParen => write!(f, "unmatched `)`"),
+            UnmatchedBracket => write!(f, "unmatched `]`"),
+            UnmatchedBrace => write!(f, "unmatched `}}`"),
+            EmptyClass => write!(f, "empty class"),
+            IncompleteClassRange(c) => {
+                write!(f, "expected class range character, found {}", Found(*c))
+            }
+            InvalidClassRange(a, b) => write!(f, "class range `{a}-{b}` is out of order"),
+            InvalidRepetition(c) => write!(f, "expected repetition count, found {}", Found(*c)),
+            UnclosedRepetition(c) => write!(f, "expected `}}` or `,`, found {}", Found(*c)),
+            RepetitionTooLarge => write!(f, "repetition count exceeds {}", u16::MAX),
+            InvalidRepetitionRange(x, y) => {
+                write!(f, "repetition range {{{x},{y}}} is out of order")
+            }
+            DuplicateGroupName(x) => write!(f, "duplicate group name `{x}`"),
+            UnknownProperty(x) => write!(f, "unknown Unicode property `{x}`"),
+            UnknownPosixClass(x) => write!(f, "unknown POSIX class `{x}`"),
+            NestLimitExceeded(x) => write!(f, "nesting exceeds the limit of {x}"),
+
+            SizeLimitExceeded(x) => write!(f, "compiled regex exceeds the limit of {x} nodes"),
+            StateLimitExceeded(x) => write!(f, "DFA exceeds the limit of {x} states"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexError {
+    kind: ErrorKind,
+    span: Span,
+    pattern: String,
+}
+
+impl RegexError {
+    pub(crate) fn new(kind: ErrorKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            pattern: String::new(),
+        }
+    }
+
+    pub(crate) fn with_pattern(mut self, pattern: &str) -> Self {
+        self.pattern = pattern.to_string();
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        // Byte offsets into the pattern
+        self.span.start..self.span.end
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+impl Display for RegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Renders the offending line of the pattern with a caret underline.
+        //
+        // error: quantifier `*` has nothing to repeat at 0
+        //   |
+        // 1 | *a
+        //   | ^
+        write!(f, "error: {} at {}", self.kind, self.span.start)?;
+
+        if self.pattern.is_empty() {
+            return Ok(());
+        }
+
+        let line_start = self.pattern[..self.span.start]
+            .rfind('\n')
+            .map_or(0, |x| x + 1);
+
+        let line_end = self.pattern[line_start..]
+            .find('\n')
+            .map_or(self.pattern.len(), |x| line_start + x);
+
+        let line_no = self.pattern[..line_start].matches('\n').count() + 1;
+        let line = &self.pattern[line_start..line_end];
+
+        let offset = self.pattern[line_start..self.span.start].chars().count();
+        let width = self.pattern[self.span.start..self.span.end.min(line_end)]
+            .chars()
+            .count()
+            .max(1);
+
+        let pad = " ".repeat(line_no.to_string().len());
+
+        writeln!(f)?;
+        writeln!(f, "{pad} |")?;
+        writeln!(f, "{line_no} | {line}")?;
+        write!(f, "{pad} | {}{}", " ".repeat(offset), "^".repeat(width))
+    }
+}
+
+impl Error for RegexError {}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use crate::nfa::Nfa;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    // Which match wins among those with the leftmost start.
+    //
+    // Highest priority, earlier alternatives and greedy or lazy as written, like Perl
+    #[default]
+    LeftmostFirst = 0,
+
+    // Longest, like POSIX, lazy quantifiers only change the groups
+    LeftmostLongest = 1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'t> {
+    text: &'t str,
+    start: usize,
+    end: usize,
+}
+
+impl<'t> Match<'t> {
+    pub(crate) fn new(text: &'t str, start: usize, end: usize) -> Self {
+        Self { text, start, end }
+    }
+
+    pub fn start(&self) -> usize {
+        // Byte offset of the start
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        // Byte offset after the end
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn as_str(&self) -> &'t str {
+        &self.text[self.range()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn char_range(&self) -> Range<usize> {
+        // Char offsets instead of byte offsets, counts from the start of the text
+        let start = self.text[..self.start].chars().count();
+        start..start + self.as_str().chars().count()
+    }
+}
+
+pub struct FindIter<'r, 't> {
+    nfa: &'r Nfa,
+    text: &'t str,
+
+    // Byte offset to search from
+    next: usize,
+
+    // End of the previous match
+    last: Option<usize>,
+}
+
+impl<'r, 't> FindIter<'r, 't> {
+    pub(crate) fn new(nfa: &'r Nfa, text: &'t str) -> Self {
+        Self {
+            nfa,
+            text,
+            next: 0,
+            last: None,
+        }
+    }
+}
+
+impl<'t> Iterator for FindIter<'_, 't> {
+    type Item = Match<'t>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next <= self.text.len() {
+            let (start, end) = self.nfa.search(self.text, self.next)?;
+
+            // Empty matches always move forward by one char
+            self.next = if start == end {
+                let c = self.text[end..].chars().next();
+                end + c.map_or(1, char::len_utf8)
+            } else {
+                end
+            };
+
+            // Skip empty matches right after the previous match,
+            // such as the end of `aa` for `a*` on "aab"
+            if start == end && self.last == Some(end) {
+                continue;
+            }
+
+            self.last = Some(end);
+            return Some(Match::new(self.text, start, end));
+        }
+
+        None
+    }
+}
+
+pub struct Split<'r, 't> {
+    // Text between the matches of `find_iter`
+    matches: FindIter<'r, 't>,
+
+    // Byte offset after the previous match, none once the rest is yielded
+    last: Option<usize>,
+}
+
+impl<'r, 't> Split<'r, 't> {
+    pub(crate) fn new(nfa: &'r Nfa, text: &'t str) -> Self {
+        Self {
+            matches: FindIter::new(nfa, text),
+            last: Some(0),
+        }
+    }
+
+    fn rest(&mut self) -> Option<&'t str> {
+        // Text after the previous match, only once
+        let text = self.matches.text;
+        self.last.take().map(|x| &text[x..])
+    }
+}
+
+impl<'t> Iterator for Split<'_, 't> {
+    type Item = &'t str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let last = self.last?;
+
+        match self.matches.next() {
+            Some(m) => {
+                self.last = Some(m.end());
+                Some(&self.matches.text[last..m.start()])
+            }
+            None => self.rest(),
+        }
+    }
+}
+
+pub struct SplitN<'r, 't> {
+    split: Split<'r, 't>,
+
+    // Fields left to yield, the last being the rest of the text
+    left: usize,
+}
+
+impl<'r, 't> SplitN<'r, 't> {
+    pub(crate) fn new(nfa: &'r Nfa, text: &'t str, limit: usize) -> Self {
+        Self {
+            split: Split::new(nfa, text),
+            left: limit,
+        }
+    }
+}
+
+impl<'t> Iterator for SplitN<'_, 't> {
+    type Item = &'t str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.left = self.left.checked_sub(1)?;
+
+        if self.left == 0 {
+            self.split.rest()
+        } else {
+            self.split.next()
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use std::ops::BitOr;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flags(u8);
+
+impl Flags {
+    pub const CASE_INSENSITIVE: Flags = Flags(1 << 0);
+    pub const MULTI_LINE: Flags = Flags(1 << 1);
+    pub const DOT_ALL: Flags = Flags(1 << 2);
+    pub const IGNORE_WHITESPACE: Flags = Flags(1 << 3);
+
+    // On by default, turned off with `(?-u)`
+    pub const UNICODE: Flags = Flags(1 << 4);
+
+    // `.` also excludes `\r`
+    pub const CRLF: Flags = Flags(1 << 5);
+
+    pub fn from_char(c: char) -> Option<Self> {
+        // Inline flag letter, as in `(?i)`
+        match c {
+            'i' => Some(Self::CASE_INSENSITIVE),
+            'm' => Some(Self::MULTI_LINE),
+            's' => Some(Self::DOT_ALL),
+            'x' => Some(Self::IGNORE_WHITESPACE),
+            'u' => Some(Self::UNICODE),
+            'R' => Some(Self::CRLF),
+            _ => None,
+        }
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn set(&mut self, other: Self, on: bool) {
+        if on {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl BitOr for Flags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FlagChange {
+    // Flags turned on and off, as in `(?on-off)`
+    pub on: Flags,
+    pub off: Flags,
+}
+
+impl FlagChange {
+    pub fn apply(self, flags: Flags) -> Flags {
+        Flags((flags.0 | self.on.0) & !self.off.0)
+    }
+}
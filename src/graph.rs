@@ -1,30 +1,78 @@
 use std::fmt::Display;
 
-use crate::set::Set as State;
+use crate::look::Look;
 
 pub type Edge<T> = (T, usize);
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Label<T> {
+    // Transition of an NFA with epsilons
+    Value(T),
+    Epsilon,
+    Save(usize),
+    Look(Look),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Step<T> {
+    // Transition of an NFA without epsilons.
+    //
+    // `saves` are the capture slots passed over before consuming `value`,
+    // `look` the assertions passed over, which must hold before consuming.
+    // No value means the end state is reached without consuming anything.
+    pub value: Option<T>,
+    pub saves: Vec<usize>,
+    pub look: Look,
+}
+
 #[derive(Debug)]
 pub struct Node<T> {
     pub edges: Vec<Edge<T>>,
+
+    // Index into `Graph::repeats` for counter states
+    pub repeat: Option<usize>,
 }
 
 impl<T> Default for Node<T> {
     // Apparently derive default requires T to have default?
     fn default() -> Self {
-        Self { edges: Vec::new() }
+        Self {
+            edges: Vec::new(),
+            repeat: None,
+        }
     }
 }
 
+#[derive(Debug)]
+pub struct Repeat<T> {
+    // Counted repetition of a single transition, as counter states.
+    //
+    // Node `base + i` has consumed `label` i times. Edges are computed
+    // rather than stored, so the repetition costs no copies of `label`.
+    // Counts below `upper` consume into the next count, an unbounded
+    // repeat stays at `lower`. Counts from `lower` also take the edges of `exit`,
+    // after consuming if `greedy` and before otherwise.
+    pub base: usize,
+    pub lower: usize,
+    pub upper: Option<usize>,
+    pub greedy: bool,
+    pub label: T,
+    pub exit: usize,
+}
+
 #[derive(Debug)]
 pub struct Graph<T> {
     pub nodes: Vec<Node<T>>,
+    pub repeats: Vec<Repeat<T>>,
 }
 
 impl<T> Default for Graph<T> {
     // Apparently having all fields impl Default isn't enough??
     fn default() -> Self {
-        Self { nodes: Vec::new() }
+        Self {
+            nodes: Vec::new(),
+            repeats: Vec::new(),
+        }
     }
 }
 
@@ -40,40 +88,104 @@ impl<T> Graph<T> {
         self.nodes[start].edges.push((value, end));
     }
 
+    pub fn add_repeat(&mut self, repeat: Repeat<T>) {
+        // Marks the counter states of `repeat`, which must already exist
+        let states = repeat.upper.unwrap_or(repeat.lower) + 1;
+
+        for node in &mut self.nodes[repeat.base..repeat.base + states] {
+            node.repeat = Some(self.repeats.len());
+        }
+
+        self.repeats.push(repeat);
+    }
+
+    pub fn edges(&self, node: usize) -> impl Iterator<Item = (&T, usize)> {
+        // Edges in priority order, including those of counter states
+        let repeat = self.nodes[node].repeat.map(|x| &self.repeats[x]);
+
+        let next = repeat.and_then(|x| {
+            let count = node - x.base;
+
+            match x.upper {
+                Some(upper) if count < upper => Some((&x.label, node + 1)),
+                Some(_) => None,
+                None => Some((&x.label, node + usize::from(count < x.lower))),
+            }
+        });
+
+        let exit = match repeat {
+            Some(x) if node - x.base >= x.lower => &self.nodes[x.exit].edges[..],
+            _ => &[],
+        };
+
+        let greedy = repeat.is_none_or(|x| x.greedy);
+        let (first, last) = if greedy { (next, None) } else { (None, next) };
+
+        self.nodes[node]
+            .edges
+            .iter()
+            .map(|(x, next)| (x, *next))
+            .chain(first)
+            .chain(exit.iter().map(|(x, next)| (x, *next)))
+            .chain(last)
+    }
+
     pub fn len(&self) -> usize {
         self.nodes.len()
     }
 }
 
-impl<T> Graph<Option<T>> {
+impl<T> Graph<Label<T>> {
     pub fn add_e(&mut self, start: usize, end: usize) {
         // Add an epsilon edge
-        self.nodes[start].edges.push((None, end));
+        self.nodes[start].edges.push((Label::Epsilon, end));
+    }
+
+    pub fn add_save(&mut self, start: usize, end: usize, slot: usize) {
+        // Add an epsilon edge which records the position in `slot`
+        self.nodes[start].edges.push((Label::Save(slot), end));
+    }
+
+    pub fn add_look(&mut self, start: usize, end: usize, look: Look) {
+        // Add an epsilon edge which only passes if `look` holds
+        self.nodes[start].edges.push((Label::Look(look), end));
+    }
+}
+
+impl<T: Display> Display for Label<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Label::Value(x) => write!(f, "{x}"),
+            Label::Epsilon => write!(f, "ε"),
+            Label::Save(x) => write!(f, "s{x}"),
+            Label::Look(x) => write!(f, "{x}"),
+        }
     }
+}
 
-    pub fn traverse(&self, node: usize, seen: &mut State) {
-        // Performs DFS on reachable nodes via epsilon.
-        if seen.contains(node) {
-            return;
+impl<T: Display> Display for Step<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for slot in &self.saves {
+            write!(f, "s{slot}:")?;
         }
 
-        seen.insert(node);
+        if !self.look.is_empty() {
+            write!(f, "{}:", self.look)?;
+        }
 
-        // Fine to iterate as graph is sparse
-        for edge in &self.nodes[node].edges {
-            if edge.0.is_none() {
-                self.traverse(edge.1, seen);
-            }
+        match &self.value {
+            Some(x) => write!(f, "{x}"),
+            None => write!(f, "ε"),
         }
     }
 }
 
 impl<T: Display> Display for Graph<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (idx, node) in self.nodes.iter().enumerate() {
+        for idx in 0..self.len() {
             write!(f, "{idx}: ")?;
 
-            for (value, next_node) in &node.edges {
+            for (value, next_node) in self.edges(idx) {
                 write!(f, "{value}_")?;
                 write!(f, "{next_node} ")?;
             }
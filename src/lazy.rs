@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::alphabet::Alphabet;
+use crate::graph::{Graph, Step};
+use crate::set::Set;
+use crate::value::Value;
+
+// Approximate bytes of cached states before the cache is cleared
+const CACHE_LIMIT: usize = 1 << 21;
+
+// Clears allowed in one run before checking for thrashing
+const MIN_CLEARS: usize = 3;
+
+// Chars scanned per state built, below which the cache is thrashing
+const MIN_CHARS_PER_STATE: usize = 10;
+
+// NFA nodes stepped per state allowed when exploring, as a state with many
+// nodes costs as much to build as many small ones
+const EXPLORE_WORK_PER_STATE: usize = 200;
+
+// Transition not computed yet
+const UNKNOWN: u32 = u32::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mode {
+    // Match of the whole text
+    Anchored,
+
+    // Any match, with a thread started before every char
+    Unanchored,
+
+    // End of the leftmost-first match. Threads are started until a match,
+    // and lower priority threads dropped at each match
+    LeftmostFirst,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Key {
+    // NFA nodes in priority order
+    nodes: Box<[usize]>,
+
+    // Stands for the previous char in assertions
+    prev: Option<char>,
+    mode: Mode,
+    matched: bool,
+}
+
+#[derive(Debug)]
+struct Cache {
+    ids: HashMap<Key, u32>,
+    keys: Vec<Key>,
+
+    // Transition of state `i` on class `j` at `trans[i * classes + j]`,
+    // the next state shifted left, the low bit set if accepting before the char
+    trans: Vec<u32>,
+
+    // Whether each state accepts at the end of the text, once computed
+    ends: Vec<Option<bool>>,
+
+    // Approximate bytes used
+    size: usize,
+
+    // Scratch space for the nodes of a new state
+    set: Set,
+}
+
+impl Cache {
+    fn new(nodes: usize) -> Self {
+        Self {
+            ids: HashMap::new(),
+            keys: Vec::new(),
+            trans: Vec::new(),
+            ends: Vec::new(),
+            size: 0,
+            set: Set::new(nodes),
+        }
+    }
+
+    fn add(&mut self, key: Key, classes: usize) -> usize {
+        // Id of the state, added with no transitions if new
+        if let Some(&id) = self.ids.get(&key) {
+            return id as usize;
+        }
+
+        let id = self.keys.len();
+
+        self.size += 2 * size_of_val(&*key.nodes) + 4 * classes + 64;
+        self.ids.insert(key.clone(), id as u32);
+        self.keys.push(key);
+        self.trans.extend(std::iter::repeat_n(UNKNOWN, classes));
+        self.ends.push(None);
+
+        id
+    }
+
+    fn reset(&mut self, id: usize, classes: usize) -> usize {
+        // Empties the cache except for state `id`, returning its new id
+        let key = self.keys[id].clone();
+
+        self.ids.clear();
+        self.keys.clear();
+        self.trans.clear();
+        self.ends.clear();
+        self.size = 0;
+
+        self.add(key, classes)
+    }
+}
+
+pub struct Explored {
+    // Every state reachable from the starts, see `Cache` for `trans`
+    pub starts: Vec<char>,
+    pub trans: Vec<u32>,
+    pub ends: Vec<bool>,
+    pub anchored: usize,
+    pub unanchored: usize,
+
+    // State with no threads left, if reached
+    pub dead: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LazyDfa {
+    // DFA built from an NFA as the text is scanned, one state per set of NFA nodes.
+    // Chars are looked up by class, so states have a transition per class
+    alphabet: Alphabet,
+    cache: Mutex<Cache>,
+    limit: usize,
+}
+
+impl LazyDfa {
+    pub fn new(graph: &Graph<Step<Value>>) -> Self {
+        Self::with_limit(graph, CACHE_LIMIT)
+    }
+
+    fn with_limit(graph: &Graph<Step<Value>>, limit: usize) -> Self {
+        Self {
+            alphabet: Alphabet::new(graph),
+            cache: Mutex::new(Cache::new(graph.len())),
+            limit,
+        }
+    }
+
+    pub fn explore(graph: &Graph<Step<Value>>, start: usize, limit: usize) -> Option<Explored> {
+        // Builds every state reachable from an anchored and an unanchored start,
+        // none if there are more than `limit` or they take too long to build.
+        let dfa = Self::with_limit(graph, usize::MAX);
+        let classes = dfa.alphabet.len();
+        let mut cache = dfa.cache.lock().unwrap();
+
+        let key = |mode| Key {
+            nodes: Box::new([start]),
+            prev: None,
+            mode,
+            matched: false,
+        };
+
+        let anchored = cache.add(key(Mode::Anchored), classes);
+        let unanchored = cache.add(key(Mode::Unanchored), classes);
+
+        let mut id = 0;
+        let mut work = limit.saturating_mul(EXPLORE_WORK_PER_STATE);
+
+        while id < cache.keys.len() {
+            for class in 0..classes {
+                work = work.checked_sub(cache.keys[id].nodes.len() + 1)?;
+                dfa.step(graph, start, &mut cache, id, class);
+
+                if cache.keys.len() > limit {
+                    return None;
+                }
+            }
+
+            dfa.accepts_end(graph, &mut cache, id);
+            id += 1;
+        }
+
+        Some(Explored {
+            starts: (0..classes)
+                .map(|x| dfa.alphabet.representative(x))
+                .collect(),
+            trans: std::mem::take(&mut cache.trans),
+            ends: cache.ends.iter().map(|x| x.unwrap()).collect(),
+            anchored,
+            unanchored,
+            dead: cache.keys.iter().position(|x| x.nodes.is_empty()),
+        })
+    }
+
+    pub fn run(
+        &self,
+        graph: &Graph<Step<Value>>,
+        start: usize,
+        inp: &str,
+        from: usize,
+        mode: Mode,
+    ) -> Option<Option<usize>> {
+        // Scans from byte `from`, returning the end of the last match seen.
+        // Anchored runs only accept at the end, unanchored runs stop at the first match.
+        // None if the cache thrashes, which leaves the text to the NFA.
+        Some(self.scan(graph, start, inp, from, mode)?.0)
+    }
+
+    pub fn leftmost(
+        &self,
+        graph: &Graph<Step<Value>>,
+        start: usize,
+        inp: &str,
+        from: usize,
+    ) -> Option<Option<(usize, usize)>> {
+        // Bounds of the leftmost-first match, the start no later than the
+        // match's and the end exact
+        let (end, restart) = self.scan(graph, start, inp, from, Mode::LeftmostFirst)?;
+        Some(end.map(|x| (restart, x)))
+    }
+
+    fn scan(
+        &self,
+        graph: &Graph<Step<Value>>,
+        start: usize,
+        inp: &str,
+        from: usize,
+        mode: Mode,
+    ) -> Option<(Option<usize>, usize)> {
+        // Same as `run`, also returning the last byte where every thread
+        // before it had died, so no match starts earlier
+        let mut guard;
+        let mut local;
+
+        let cache: &mut Cache = match self.cache.try_lock() {
+            Ok(x) => {
+                guard = x;
+                &mut guard
+            }
+            // Used by another thread, states found here aren't kept
+            Err(_) => {
+                local = Cache::new(graph.len());
+                &mut local
+            }
+        };
+
+        let classes = self.alphabet.len();
+
+        let key = Key {
+            nodes: Box::new([start]),
+            prev: self.alphabet.prev(inp[..from].chars().next_back()),
+            mode,
+            matched: false,
+        };
+
+        let mut id = cache.add(key, classes);
+        let mut last = None;
+        let mut restart = from;
+
+        let mut clears = 0;
+        let mut built = 0;
+
+        for (index, c) in inp[from..].char_indices() {
+            let index = from + index;
+            let class = self.alphabet.class(c);
+            let mut next = cache.trans[id * classes + class];
+
+            if next == UNKNOWN {
+                if cache.size > self.limit {
+                    clears += 1;
+
+                    if clears > MIN_CLEARS && index - from < MIN_CHARS_PER_STATE * built {
+                        return None;
+                    }
+
+                    id = cache.reset(id, classes);
+                }
+
+                next = self.step(graph, start, cache, id, class);
+                built += 1;
+            }
+
+            if next & 1 == 1 && mode != Mode::Anchored {
+                last = Some(index);
+
+                if mode == Mode::Unanchored {
+                    return Some((last, restart));
+                }
+            }
+
+            id = (next >> 1) as usize;
+
+            // No threads left to match
+            if cache.keys[id].nodes.is_empty() {
+                return Some((last, restart));
+            }
+
+            // Only the thread started here, nothing points back at the start
+            if *cache.keys[id].nodes == [start] {
+                restart = index + c.len_utf8();
+            }
+        }
+
+        if self.accepts_end(graph, cache, id) {
+            last = Some(inp.len());
+        }
+
+        Some((last, restart))
+    }
+
+    fn step(
+        &self,
+        graph: &Graph<Step<Value>>,
+        start: usize,
+        cache: &mut Cache,
+        id: usize,
+        class: usize,
+    ) -> u32 {
+        // Computes and caches the transition of state `id` on `class`,
+        // like one step of the NFA on the class's first char
+        let classes = self.alphabet.len();
+        let c = self.alphabet.representative(class);
+        let key = &cache.keys[id];
+
+        let mut accepted = false;
+        cache.set.clear();
+
+        'nodes: for &node in &key.nodes {
+            for (step, next) in graph.edges(node) {
+                match &step.value {
+                    None if step.look.matches(key.prev, Some(c)) => {
+                        accepted = true;
+
+                        // Lower priority threads can't change a leftmost-first match
+                        if key.mode == Mode::LeftmostFirst {
+                            break 'nodes;
+                        }
+                    }
+                    Some(x) if x.matches(c) && step.look.matches(key.prev, Some(c)) => {
+                        cache.set.insert(next);
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let mode = key.mode;
+        let matched = key.matched || (accepted && mode == Mode::LeftmostFirst);
+
+        // Later starts have the lowest priority
+        if mode != Mode::Anchored && !matched {
+            cache.set.insert(start);
+        }
+
+        let key = Key {
+            nodes: cache.set.usizes.as_slice().into(),
+            prev: self.alphabet.prev(Some(c)),
+            mode,
+            matched,
+        };
+
+        let next = (cache.add(key, classes) as u32) << 1 | u32::from(accepted);
+        cache.trans[id * classes + class] = next;
+
+        next
+    }
+
+    fn accepts_end(&self, graph: &Graph<Step<Value>>, cache: &mut Cache, id: usize) -> bool {
+        if let Some(x) = cache.ends[id] {
+            return x;
+        }
+
+        let key = &cache.keys[id];
+
+        let accepts = key
+            .nodes
+            .iter()
+            .flat_map(|&x| graph.edges(x))
+            .any(|(step, _)| step.value.is_none() && step.look.matches(key.prev, None));
+
+        cache.ends[id] = Some(accepts);
+        accepts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::{DEFAULT_SIZE_LIMIT, compile};
+    use crate::find::MatchKind;
+    use crate::flags::Flags;
+    use crate::lexer::lexer;
+    use crate::nfa::Nfa;
+    use crate::parser::{DEFAULT_NEST_LIMIT, parse};
+
+    fn nfa(pattern: &str) -> Nfa {
+        let tokens = lexer(pattern, Flags::UNICODE).unwrap();
+        let tree = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT).unwrap();
+
+        compile(&tree, DEFAULT_SIZE_LIMIT, MatchKind::LeftmostFirst).unwrap()
+    }
+
+    #[test]
+    fn modes() {
+        let nfa = nfa("ab+");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::new(graph);
+
+        let run = |inp, mode| dfa.run(graph, start, inp, 0, mode).unwrap();
+
+        assert_eq!(run("abb", Mode::Anchored), Some(3));
+        assert_eq!(run("abbc", Mode::Anchored), None);
+        assert_eq!(run("cabbc", Mode::Unanchored), Some(3));
+        assert_eq!(run("cabbc", Mode::LeftmostFirst), Some(4));
+        assert_eq!(run("cac", Mode::LeftmostFirst), None);
+    }
+
+    #[test]
+    fn leftmost_bounds() {
+        let nfa = nfa("a+b|ac");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::new(graph);
+
+        let run = |inp| dfa.leftmost(graph, start, inp, 0).unwrap();
+
+        assert_eq!(run("xxaaab"), Some((2, 6)));
+        assert_eq!(run("xaxac"), Some((3, 5)));
+        assert_eq!(run("xaaxx"), None);
+    }
+
+    #[test]
+    fn reuses_states() {
+        let nfa = nfa("[a-z]+1");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::new(graph);
+
+        dfa.run(graph, start, "abcdefgh1", 0, Mode::LeftmostFirst);
+        let states = dfa.cache.lock().unwrap().keys.len();
+
+        dfa.run(graph, start, "hgfedcba1", 0, Mode::LeftmostFirst);
+        assert_eq!(dfa.cache.lock().unwrap().keys.len(), states);
+    }
+
+    #[test]
+    fn assertions() {
+        let nfa = nfa(r"\bfoo\b");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::new(graph);
+
+        let run = |inp, from| {
+            dfa.run(graph, start, inp, from, Mode::LeftmostFirst)
+                .unwrap()
+        };
+
+        assert_eq!(run("a foo b", 0), Some(5));
+        assert_eq!(run("afoo foo", 0), Some(8));
+        assert_eq!(run("éfoo", 0), None);
+        assert_eq!(run("afoo", 1), None);
+    }
+
+    #[test]
+    fn thrashing_gives_up() {
+        // Every char of the text makes a new state
+        let nfa = nfa("(a|b)*a(a|b){10}");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::with_limit(graph, 0);
+
+        let text = "ab".repeat(20) + &"aabba".repeat(20);
+        assert_eq!(dfa.run(graph, start, &text, 0, Mode::Unanchored), None);
+    }
+
+    #[test]
+    fn clearing_keeps_working() {
+        let nfa = nfa("a+b");
+        let (graph, start) = nfa.parts();
+        let dfa = LazyDfa::with_limit(graph, 0);
+
+        let text = "a".repeat(100) + "b";
+        assert_eq!(
+            dfa.run(graph, start, &text, 0, Mode::Anchored),
+            Some(Some(101))
+        );
+    }
+}
@@ -1,115 +1,356 @@
-use crate::token::Token;
+use crate::error::{ErrorKind, RegexError};
+use crate::flags::{FlagChange, Flags};
+use crate::token::{Span, Token, TokenKind};
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum LexerMode {
     Normal,
     Escaped,
-    Hex(u8),
+
+    // Digits left, and whether to return to a class
+    Hex(u8, bool),
     Class,
     ClassEscaped,
+
+    // Whether negated, and whether to return to a class
+    PropertyOpen(bool, bool),
+    PropertyName(bool, bool),
+
+    // `[:name:]` inside a class
+    PosixName,
+    PosixClose,
+    Extension,
+    NameOpen,
+    Name(usize),
+    FlagSet(FlagChange, bool),
+
+    // Whether to return to a class
+    Comment(bool),
 }
 
-const SYNTAX: &str = "|?+*{}()[]\\";
+const SYNTAX: &str = "|?+*{}()[]^$.\\";
+
+// Letter escapes passed on to the parser
+const ESCAPES: &str = "AzbBdDwWsS";
+
+// Letter escapes allowed inside a class
+const CLASS_ESCAPES: &str = "dDwWsS";
+
+fn valid_name(name: &str) -> bool {
+    // Identifier made of letters, digits and `_`, not starting with a digit
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c == '_' || c.is_alphabetic())
+        && chars.all(|c| c == '_' || c.is_alphanumeric())
+}
 
-pub fn lexer(input: &str) -> Result<Vec<Token>, &'static str> {
+pub fn lexer(input: &str, flags: Flags) -> Result<Vec<Token>, RegexError> {
+    use ErrorKind::*;
     use LexerMode::*;
-    use Token::*;
+    use TokenKind::*;
 
     let mut num = 0u32;
 
     let mut out = vec![];
     let mut mode = Normal;
 
-    for c in input.chars() {
+    // Start of the current escape sequence
+    let mut start = 0;
+
+    // Start of the outermost class, for unclosed class errors
+    let mut class = 0;
+
+    // Number of nested classes open
+    let mut depth = 0;
+
+    // Name of the current group, property or POSIX class
+    let mut name = String::new();
+
+    // Flags of each open group, only `x` matters to the lexer
+    let mut flags = vec![flags];
+
+    let err = |kind, start, end| RegexError::new(kind, Span::new(start, end));
+
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        let end = idx + c.len_utf8();
+        let span = Span::new(start, end);
+
         mode = match mode {
             Normal => {
-                if c == '\\' {
+                start = idx;
+                let span = Span::new(idx, end);
+
+                let verbose = flags.last().unwrap().contains(Flags::IGNORE_WHITESPACE);
+
+                if verbose && c.is_whitespace() {
+                    Normal
+                } else if verbose && c == '#' {
+                    Comment(false)
+                } else if c == '\\' {
                     Escaped
+                } else if c == '(' && chars.next_if(|x| x.1 == '?').is_some() {
+                    Extension
+                } else if c == '(' {
+                    flags.push(*flags.last().unwrap());
+                    out.push(Token::new(Syntax(b'('), span));
+                    Normal
+                } else if c == ')' {
+                    if flags.len() > 1 {
+                        flags.pop();
+                    }
+
+                    out.push(Token::new(Syntax(b')'), span));
+                    Normal
                 } else if c == '[' {
-                    out.push(Syntax(c as u8));
+                    class = idx;
+                    depth = 1;
+                    out.push(Token::new(Syntax(c as u8), span));
                     Class
                 } else if SYNTAX.contains(c) {
-                    out.push(Syntax(c as u8));
+                    out.push(Token::new(Syntax(c as u8), span));
                     Normal
                 } else {
-                    out.push(Literal(c));
+                    out.push(Token::new(Literal(c), span));
                     Normal
                 }
             }
 
-            Escaped => {
+            m @ (Escaped | ClassEscaped) => {
+                let (next, escapes) = match m {
+                    Escaped => (Normal, ESCAPES),
+                    _ => (Class, CLASS_ESCAPES),
+                };
+
                 if c == 'x' {
                     num = 0;
-                    Hex(2)
+                    Hex(2, next == Class)
                 } else if c == 'u' {
                     num = 0;
-                    Hex(4)
-                } else if SYNTAX.contains(c) {
-                    out.push(Literal(c));
-                    Normal
+                    Hex(4, next == Class)
+                } else if c.is_ascii_punctuation() || c == ' ' {
+                    out.push(Token::new(Literal(c), span));
+                    next
+                } else if escapes.contains(c) {
+                    out.push(Token::new(Escape(c), span));
+                    next
+                } else if c == 'p' || c == 'P' {
+                    PropertyOpen(c == 'P', next == Class)
+                } else {
+                    return Err(err(UnknownEscape(c), start, end));
+                }
+            }
+
+            PropertyOpen(negated, in_class) => {
+                if c == '{' {
+                    PropertyName(negated, in_class)
                 } else {
-                    return Err("Unknown escaped character");
+                    out.push(Token::new(Property(c.to_string(), negated), span));
+                    if in_class { Class } else { Normal }
                 }
             }
 
-            Hex(n) => {
+            PropertyName(negated, in_class) => {
+                if c == '}' {
+                    let name = std::mem::take(&mut name);
+                    out.push(Token::new(Property(name, negated), span));
+                    if in_class { Class } else { Normal }
+                } else {
+                    name.push(c);
+                    PropertyName(negated, in_class)
+                }
+            }
+
+            Hex(n, in_class) => {
                 if let Some(x) = c.to_digit(16) {
                     num <<= 4;
                     num += x;
                 } else {
-                    return Err("Expected hex character");
+                    return Err(err(ExpectedHex(Some(c)), idx, end));
                 }
 
                 if n <= 1 {
-                    let c = char::from_u32(num).ok_or("Invalid char")?;
-                    out.push(Literal(c));
-                    Normal
+                    let c = char::from_u32(num)
+                        .ok_or_else(|| err(InvalidCodepoint(num), start, end))?;
+                    out.push(Token::new(Literal(c), span));
+                    if in_class { Class } else { Normal }
                 } else {
-                    Hex(n - 1)
+                    Hex(n - 1, in_class)
                 }
             }
 
             Class => {
-                if c == ']' {
-                    out.push(Syntax(b']'));
-                    Normal
+                start = idx;
+                let span = Span::new(idx, end);
+
+                let verbose = flags.last().unwrap().contains(Flags::IGNORE_WHITESPACE);
+
+                // Operators are doubled, as in `&&`
+                let op = ['&', '-', '~']
+                    .contains(&c)
+                    .then(|| chars.next_if(|x| x.1 == c))
+                    .flatten();
+
+                if verbose && c.is_whitespace() {
+                    Class
+                } else if verbose && c == '#' {
+                    Comment(true)
+                } else if let Some((idx, _)) = op {
+                    let span = Span::new(start, idx + 1);
+                    out.push(Token::new(ClassOp(c), span));
+                    Class
+                } else if c == ']' {
+                    depth -= 1;
+                    out.push(Token::new(Syntax(b']'), span));
+                    if depth == 0 { Normal } else { Class }
                 } else if c == '-' {
-                    out.push(Syntax(b'-'));
+                    out.push(Token::new(Syntax(b'-'), span));
                     Class
                 } else if c == '\\' {
                     ClassEscaped
+                } else if c == '[' && chars.next_if(|x| x.1 == ':').is_some() {
+                    PosixName
+                } else if c == '[' {
+                    depth += 1;
+                    out.push(Token::new(Syntax(b'['), span));
+                    Class
                 } else {
-                    out.push(Literal(c));
+                    out.push(Token::new(Literal(c), span));
                     Class
                 }
             }
 
-            ClassEscaped => {
-                out.push(Literal(c));
+            PosixName => {
+                if c == ':' {
+                    PosixClose
+                } else if c == ']' {
+                    return Err(err(UnclosedPosixClass(Some(c)), idx, end));
+                } else {
+                    name.push(c);
+                    PosixName
+                }
+            }
+
+            PosixClose => {
+                if c != ']' {
+                    return Err(err(UnclosedPosixClass(Some(c)), idx, end));
+                }
+
+                let name = std::mem::take(&mut name);
+
+                let token = match name.strip_prefix('^') {
+                    Some(x) => Posix(x.to_string(), true),
+                    None => Posix(name, false),
+                };
+
+                out.push(Token::new(token, span));
                 Class
             }
+
+            Extension if c == 'P' => NameOpen,
+            Extension if c == '<' => Name(end),
+
+            m @ (Extension | FlagSet(..)) => {
+                let (mut change, negated) = match m {
+                    FlagSet(change, negated) => (change, negated),
+                    _ => (FlagChange::default(), false),
+                };
+
+                if c == ':' {
+                    flags.push(change.apply(*flags.last().unwrap()));
+                    out.push(Token::new(NonCapture(change), span));
+                    Normal
+                } else if c == ')' && m != Extension {
+                    let top = flags.last_mut().unwrap();
+                    *top = change.apply(*top);
+                    out.push(Token::new(SetFlags(change), span));
+                    Normal
+                } else if c == '-' && !negated {
+                    FlagSet(change, true)
+                } else if let Some(flag) = Flags::from_char(c) {
+                    if negated {
+                        change.off = change.off | flag;
+                    } else {
+                        change.on = change.on | flag;
+                    }
+
+                    FlagSet(change, negated)
+                } else {
+                    return Err(err(UnknownGroup(c), idx, end));
+                }
+            }
+
+            Comment(in_class) => {
+                if c != '\n' {
+                    Comment(in_class)
+                } else if in_class {
+                    Class
+                } else {
+                    Normal
+                }
+            }
+
+            NameOpen => {
+                if c == '<' {
+                    Name(end)
+                } else {
+                    return Err(err(UnknownGroup(c), idx, end));
+                }
+            }
+
+            Name(name_start) => {
+                if c == '>' {
+                    if !valid_name(&name) {
+                        return Err(err(InvalidGroupName(name), name_start, idx));
+                    }
+
+                    let name = std::mem::take(&mut name);
+                    flags.push(*flags.last().unwrap());
+                    out.push(Token::new(NamedGroup(name), span));
+                    Normal
+                } else {
+                    name.push(c);
+                    Name(name_start)
+                }
+            }
         };
     }
 
+    let len = input.len();
+
     match mode {
-        Normal => Ok(out),
-        Escaped => Err("Expected escape character"),
-        Hex(_) => Err("Expected hex character"),
-        Class | ClassEscaped => Err("Incomplete class"),
+        Normal | Comment(false) => Ok(out),
+        Escaped | ClassEscaped | PropertyOpen(..) => Err(err(IncompleteEscape, start, len)),
+        PropertyName(..) => Err(err(UnclosedProperty, start, len)),
+        Hex(..) => Err(err(ExpectedHex(None), len, len)),
+        Class | Comment(true) => Err(err(IncompleteClass, class, class + 1)),
+        Extension | NameOpen | FlagSet(..) => Err(err(UnclosedGroup, start, start + 1)),
+        Name(_) => Err(err(UnclosedGroupName, start, len)),
+        PosixName | PosixClose => Err(err(UnclosedPosixClass(None), len, len)),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use Token::{Literal as l, Syntax};
+    use TokenKind::{
+        ClassOp, Escape, Literal as l, NamedGroup, NonCapture, Posix, Property, SetFlags, Syntax,
+    };
 
-    fn s(a: char) -> Token {
+    fn s(a: char) -> TokenKind {
         Syntax(a as u8)
     }
 
+    fn kinds(input: &str) -> Result<Vec<TokenKind>, ErrorKind> {
+        lexer(input, Flags::UNICODE)
+            .map(|x| x.into_iter().map(|y| y.kind).collect())
+            .map_err(|e| e.kind().clone())
+    }
+
     #[test]
     fn literal() {
-        let tokens = lexer("abcd");
+        let tokens = kinds("abcd");
         let expected = Ok(vec![l('a'), l('b'), l('c'), l('d')]);
 
         assert_eq!(tokens, expected);
@@ -117,7 +358,7 @@ mod tests {
 
     #[test]
     fn syntax() {
-        let tokens = lexer("a+()?");
+        let tokens = kinds("a+()?");
         let expected = Ok(vec![l('a'), s('+'), s('('), s(')'), s('?')]);
 
         assert_eq!(tokens, expected);
@@ -125,7 +366,7 @@ mod tests {
 
     #[test]
     fn escaped_syntax() {
-        let tokens = lexer("a\\+\\\\");
+        let tokens = kinds("a\\+\\\\");
         let expected = Ok(vec![l('a'), l('+'), l('\\')]);
 
         assert_eq!(tokens, expected);
@@ -133,7 +374,7 @@ mod tests {
 
     #[test]
     fn escaped_null() {
-        let tokens = lexer("a\\x000\\u0000");
+        let tokens = kinds("a\\x000\\u0000");
         let expected = Ok(vec![l('a'), l('\x00'), l('0'), l('\u{0000}')]);
 
         assert_eq!(tokens, expected);
@@ -141,7 +382,7 @@ mod tests {
 
     #[test]
     fn escaped_hex() {
-        let tokens = lexer("a\\x120\\u1234");
+        let tokens = kinds("a\\x120\\u1234");
         let expected = Ok(vec![l('a'), l('\x12'), l('0'), l('\u{1234}')]);
 
         assert_eq!(tokens, expected);
@@ -149,7 +390,7 @@ mod tests {
 
     #[test]
     fn raw_hex() {
-        let tokens = lexer("\x12\u{1234}");
+        let tokens = kinds("\x12\u{1234}");
         let expected = Ok(vec![l('\x12'), l('\u{1234}')]);
 
         assert_eq!(tokens, expected);
@@ -157,15 +398,48 @@ mod tests {
 
     #[test]
     fn mixed_case_raw_hex() {
-        let tokens = lexer("a\\x0A\\x0a\\u000a\\u000A");
+        let tokens = kinds("a\\x0A\\x0a\\u000a\\u000A");
         let expected = Ok(vec![l('a'), l('\x0A'), l('\x0a'), l('\x0a'), l('\x0a')]);
 
         assert_eq!(tokens, expected);
     }
 
+    #[test]
+    fn anchors() {
+        let tokens = kinds("^\\Aa\\b\\B\\z$\\^");
+        let expected = Ok(vec![
+            s('^'),
+            Escape('A'),
+            l('a'),
+            Escape('b'),
+            Escape('B'),
+            Escape('z'),
+            s('$'),
+            l('^'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn dot() {
+        let tokens = kinds("a.\\.");
+        let expected = Ok(vec![l('a'), s('.'), l('.')]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn class_caret() {
+        let tokens = kinds("[a^$.]");
+        let expected = Ok(vec![s('['), l('a'), l('^'), l('$'), l('.'), s(']')]);
+
+        assert_eq!(tokens, expected);
+    }
+
     #[test]
     fn class_chars() {
-        let tokens = lexer("[abc\\-\\]]");
+        let tokens = kinds("[abc\\-\\]]");
         let expected = Ok(vec![s('['), l('a'), l('b'), l('c'), l('-'), l(']'), s(']')]);
 
         assert_eq!(tokens, expected);
@@ -173,7 +447,7 @@ mod tests {
 
     #[test]
     fn class_ranges() {
-        let tokens = lexer("[a-z\\--\\]]");
+        let tokens = kinds("[a-z\\--\\]]");
         let expected = Ok(vec![
             s('['),
             l('a'),
@@ -188,28 +462,323 @@ mod tests {
         assert_eq!(tokens, expected);
     }
 
+    #[test]
+    fn perl_classes() {
+        let tokens = kinds("\\d\\W[\\s_\\x41]");
+        let expected = Ok(vec![
+            Escape('d'),
+            Escape('W'),
+            s('['),
+            Escape('s'),
+            l('_'),
+            l('A'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn properties() {
+        let tokens = kinds("\\p{Greek}\\PL[\\p{Lu}a]");
+        let expected = Ok(vec![
+            Property("Greek".to_string(), false),
+            Property("L".to_string(), true),
+            s('['),
+            Property("Lu".to_string(), false),
+            l('a'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn invalid_property() {
+        assert_eq!(kinds("\\p{Lu"), Err(ErrorKind::UnclosedProperty));
+        assert_eq!(kinds("\\p"), Err(ErrorKind::IncompleteEscape));
+        assert_eq!(lexer("a\\p{Lu", Flags::UNICODE).unwrap_err().span(), 1..6);
+    }
+
+    #[test]
+    fn posix_classes() {
+        let tokens = kinds("[[:alpha:]_[:^digit:]][:a]");
+        let expected = Ok(vec![
+            s('['),
+            Posix("alpha".to_string(), false),
+            l('_'),
+            Posix("digit".to_string(), true),
+            s(']'),
+            s('['),
+            l(':'),
+            l('a'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn invalid_posix_class() {
+        let e = ErrorKind::UnclosedPosixClass;
+        assert_eq!(kinds("[[:alpha]"), Err(e(Some(']'))));
+        assert_eq!(kinds("[[:alpha:a]"), Err(e(Some('a'))));
+        assert_eq!(kinds("[[:alpha"), Err(e(None)));
+    }
+
+    #[test]
+    fn nested_classes() {
+        let tokens = kinds("[a[^b]]c]");
+        let expected = Ok(vec![
+            s('['),
+            l('a'),
+            s('['),
+            l('^'),
+            l('b'),
+            s(']'),
+            s(']'),
+            l('c'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn class_operators() {
+        let tokens = kinds("[a&&b--c~~d&e-f~]");
+        let expected = Ok(vec![
+            s('['),
+            l('a'),
+            ClassOp('&'),
+            l('b'),
+            ClassOp('-'),
+            l('c'),
+            ClassOp('~'),
+            l('d'),
+            l('&'),
+            l('e'),
+            s('-'),
+            l('f'),
+            l('~'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn invalid_nested_class() {
+        assert_eq!(lexer("a[b[c]", Flags::UNICODE).unwrap_err().span(), 1..2);
+    }
+
+    #[test]
+    fn invalid_class_escape() {
+        assert_eq!(kinds("[\\q]"), Err(ErrorKind::UnknownEscape('q')));
+        assert_eq!(kinds("[\\b]"), Err(ErrorKind::UnknownEscape('b')));
+        assert_eq!(lexer("a[b\\q]", Flags::UNICODE).unwrap_err().span(), 3..5);
+    }
+
     #[test]
     fn invalid_hanging_escape() {
-        assert!(lexer("abc\\").is_err());
+        assert_eq!(kinds("abc\\"), Err(ErrorKind::IncompleteEscape));
+    }
+
+    #[test]
+    fn invalid_unknown_escape() {
+        assert_eq!(kinds("\\q"), Err(ErrorKind::UnknownEscape('q')));
+    }
+
+    #[test]
+    fn invalid_surrogate() {
+        assert_eq!(kinds("\\ud800"), Err(ErrorKind::InvalidCodepoint(0xd800)));
     }
 
     #[test]
     fn invalid_incomplete_hex() {
-        assert!(lexer("abc\\x0").is_err());
+        assert!(lexer("abc\\x0", Flags::UNICODE).is_err());
     }
 
     #[test]
     fn invalid_incomplete_unicode() {
-        assert!(lexer("abc\\u00").is_err());
+        assert!(lexer("abc\\u00", Flags::UNICODE).is_err());
     }
 
     #[test]
     fn invalid_hex() {
-        assert!(lexer("abc\\xhh").is_err());
+        assert!(lexer("abc\\xhh", Flags::UNICODE).is_err());
     }
 
     #[test]
     fn invalid_class() {
-        assert!(lexer("[a").is_err());
+        assert!(lexer("[a", Flags::UNICODE).is_err());
+    }
+
+    #[test]
+    fn spans() {
+        let tokens = lexer("é\\x41[\\]]", Flags::UNICODE).unwrap();
+        let spans: Vec<_> = tokens.iter().map(|x| (x.span.start, x.span.end)).collect();
+
+        assert_eq!(spans, vec![(0, 2), (2, 6), (6, 7), (7, 9), (9, 10)]);
+    }
+
+    #[test]
+    fn error_spans() {
+        assert_eq!(lexer("ab\\q", Flags::UNICODE).unwrap_err().span(), 2..4);
+        assert_eq!(lexer("a\\xh1", Flags::UNICODE).unwrap_err().span(), 3..4);
+        assert_eq!(lexer("a[bc", Flags::UNICODE).unwrap_err().span(), 1..2);
+    }
+
+    #[test]
+    fn named_group() {
+        let tokens = kinds("(?<year>a)(?P<b_2>b)");
+        let expected = Ok(vec![
+            NamedGroup("year".to_string()),
+            l('a'),
+            s(')'),
+            NamedGroup("b_2".to_string()),
+            l('b'),
+            s(')'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn invalid_group_name() {
+        let e = lexer("a(?<1st>b)", Flags::UNICODE).unwrap_err();
+
+        assert_eq!(e.kind(), &ErrorKind::InvalidGroupName("1st".to_string()));
+        assert_eq!(e.span(), 4..7);
+    }
+
+    #[test]
+    fn invalid_empty_group_name() {
+        let tokens = kinds("(?P<>a)");
+        assert_eq!(tokens, Err(ErrorKind::InvalidGroupName(String::new())));
+    }
+
+    #[test]
+    fn invalid_unclosed_group_name() {
+        assert_eq!(kinds("(?<name"), Err(ErrorKind::UnclosedGroupName));
+    }
+
+    #[test]
+    fn non_capture() {
+        let tokens = kinds("(?:a)");
+        let expected = Ok(vec![NonCapture(FlagChange::default()), l('a'), s(')')]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn flags() {
+        let tokens = kinds("(?i-ms)(?s:a)");
+
+        let on = Flags::CASE_INSENSITIVE;
+        let off = Flags::MULTI_LINE | Flags::DOT_ALL;
+
+        let expected = Ok(vec![
+            SetFlags(FlagChange { on, off }),
+            NonCapture(FlagChange {
+                on: Flags::DOT_ALL,
+                off: Flags::default(),
+            }),
+            l('a'),
+            s(')'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn ignore_whitespace() {
+        let tokens = kinds("a (?x) b \\  c # comment [(\n d");
+        let on = Flags::IGNORE_WHITESPACE;
+
+        let expected = Ok(vec![
+            l('a'),
+            l(' '),
+            SetFlags(FlagChange {
+                on,
+                ..Default::default()
+            }),
+            l('b'),
+            l(' '),
+            l('c'),
+            l('d'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn scoped_ignore_whitespace() {
+        let tokens = kinds("((?x) a ) b");
+        let on = Flags::IGNORE_WHITESPACE;
+
+        let expected = Ok(vec![
+            s('('),
+            SetFlags(FlagChange {
+                on,
+                ..Default::default()
+            }),
+            l('a'),
+            s(')'),
+            l(' '),
+            l('b'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn scoped_ignore_whitespace_after_named_group() {
+        let tokens = kinds("((?<n>a)(?x) ) b");
+        let on = Flags::IGNORE_WHITESPACE;
+
+        let expected = Ok(vec![
+            s('('),
+            NamedGroup("n".to_string()),
+            l('a'),
+            s(')'),
+            SetFlags(FlagChange {
+                on,
+                ..Default::default()
+            }),
+            s(')'),
+            l(' '),
+            l('b'),
+        ]);
+
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn ignore_whitespace_in_class() {
+        let tokens = kinds("(?x)[ a # ]\n - b ]");
+        let on = Flags::IGNORE_WHITESPACE;
+
+        let expected = Ok(vec![
+            SetFlags(FlagChange {
+                on,
+                ..Default::default()
+            }),
+            s('['),
+            l('a'),
+            s('-'),
+            l('b'),
+            s(']'),
+        ]);
+
+        assert_eq!(tokens, expected);
+        assert_eq!(kinds("(?x)[a#]"), Err(ErrorKind::IncompleteClass));
+    }
+
+    #[test]
+    fn invalid_flag() {
+        assert_eq!(kinds("(?iq)"), Err(ErrorKind::UnknownGroup('q')));
+        assert_eq!(kinds("(?)"), Err(ErrorKind::UnknownGroup(')')));
+        assert_eq!(kinds("(?i"), Err(ErrorKind::UnclosedGroup));
     }
 }
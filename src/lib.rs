@@ -1,12 +1,34 @@
+mod alphabet;
 mod ast;
+mod builder;
+pub mod bytes;
+mod captures;
 mod compiler;
+mod dfa;
+mod error;
+mod find;
+mod flags;
 mod graph;
+mod lazy;
 mod lexer;
+mod look;
 mod nfa;
 mod parser;
 mod regex;
+mod replace;
+mod serialize;
 mod set;
 mod token;
+mod unicode;
+mod unicode_tables;
+mod utf8;
 mod value;
 
+pub use builder::*;
+pub use captures::*;
+pub use dfa::{DEFAULT_STATE_LIMIT, Dfa};
+pub use error::*;
+pub use find::*;
 pub use regex::*;
+pub use replace::*;
+pub use serialize::DeserializeError;
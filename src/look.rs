@@ -0,0 +1,157 @@
+use std::fmt::Display;
+use std::ops::BitOr;
+
+use crate::unicode::{is_ascii_word_char, is_word_char};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Look(u16);
+
+impl Look {
+    // Zero-width assertions, checked against the chars around a position
+    pub const START_TEXT: Look = Look(1 << 0);
+    pub const END_TEXT: Look = Look(1 << 1);
+    pub const START_LINE: Look = Look(1 << 2);
+    pub const END_LINE: Look = Look(1 << 3);
+    pub const WORD: Look = Look(1 << 4);
+    pub const NOT_WORD: Look = Look(1 << 5);
+    pub const WORD_ASCII: Look = Look(1 << 6);
+    pub const NOT_WORD_ASCII: Look = Look(1 << 7);
+
+    const NAMES: [(Look, &str); 8] = [
+        (Self::START_TEXT, "\\A"),
+        (Self::END_TEXT, "\\z"),
+        (Self::START_LINE, "(?m:^)"),
+        (Self::END_LINE, "(?m:$)"),
+        (Self::WORD, "\\b"),
+        (Self::NOT_WORD, "\\B"),
+        (Self::WORD_ASCII, "(?-u:\\b)"),
+        (Self::NOT_WORD_ASCII, "(?-u:\\B)"),
+    ];
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn from_bits(bits: u16) -> Option<Self> {
+        // Only known assertions
+        let all = Self::NAMES.iter().fold(0, |acc, x| acc | x.0.0);
+        (bits & !all == 0).then_some(Self(bits))
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn matches(self, prev: Option<char>, next: Option<char>) -> bool {
+        // Checks every assertion holds between `prev` and `next`,
+        // `None` being either end of the text.
+        if self.is_empty() {
+            return true;
+        }
+
+        let checks = [
+            (Self::START_TEXT, prev.is_none()),
+            (Self::END_TEXT, next.is_none()),
+            (Self::START_LINE, prev.is_none_or(|c| c == '\n')),
+            (Self::END_LINE, next.is_none_or(|c| c == '\n')),
+        ];
+
+        if !checks.iter().all(|(look, ok)| !self.contains(*look) || *ok) {
+            return false;
+        }
+
+        // Word chars are only looked up when needed
+        let boundary = |word: fn(char) -> bool| prev.is_some_and(word) != next.is_some_and(word);
+
+        let words = [
+            (Self::WORD, is_word_char as fn(char) -> bool, true),
+            (Self::NOT_WORD, is_word_char, false),
+            (Self::WORD_ASCII, is_ascii_word_char, true),
+            (Self::NOT_WORD_ASCII, is_ascii_word_char, false),
+        ];
+
+        words
+            .iter()
+            .all(|&(look, word, at)| !self.contains(look) || boundary(word) == at)
+    }
+}
+
+impl BitOr for Look {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl Display for Look {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (look, name) in Self::NAMES {
+            if self.contains(look) {
+                write!(f, "{name}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_always_matches() {
+        assert!(Look::default().matches(Some('a'), Some('b')));
+        assert!(Look::default().matches(None, None));
+    }
+
+    #[test]
+    fn text_boundaries() {
+        assert!(Look::START_TEXT.matches(None, Some('a')));
+        assert!(!Look::START_TEXT.matches(Some('\n'), Some('a')));
+        assert!(Look::END_TEXT.matches(Some('a'), None));
+        assert!(!Look::END_TEXT.matches(Some('a'), Some('\n')));
+    }
+
+    #[test]
+    fn line_boundaries() {
+        assert!(Look::START_LINE.matches(Some('\n'), Some('a')));
+        assert!(Look::START_LINE.matches(None, Some('a')));
+        assert!(!Look::START_LINE.matches(Some('a'), Some('\n')));
+        assert!(Look::END_LINE.matches(Some('a'), Some('\n')));
+        assert!(!Look::END_LINE.matches(Some('\n'), Some('a')));
+    }
+
+    #[test]
+    fn word_boundaries() {
+        assert!(Look::WORD.matches(None, Some('a')));
+        assert!(Look::WORD.matches(Some('a'), Some(' ')));
+        assert!(!Look::WORD.matches(Some('a'), Some('b')));
+        assert!(!Look::WORD.matches(None, None));
+        assert!(Look::NOT_WORD.matches(Some(' '), Some('-')));
+        assert!(!Look::NOT_WORD.matches(Some('a'), None));
+    }
+
+    #[test]
+    fn ascii_word_boundaries() {
+        assert!(!Look::WORD.matches(Some('a'), Some('é')));
+        assert!(Look::WORD_ASCII.matches(Some('a'), Some('é')));
+        assert!(Look::NOT_WORD_ASCII.matches(Some('é'), Some('δ')));
+    }
+
+    #[test]
+    fn combined() {
+        let look = Look::START_TEXT | Look::END_TEXT;
+        assert!(look.matches(None, None));
+        assert!(!look.matches(None, Some('a')));
+    }
+}
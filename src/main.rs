@@ -1,7 +1,7 @@
 use regex_engine::*;
 
 fn main() {
-    let r = Regex::new("[0-9]{3,}");
+    let r = Regex::new("[0-9]{3,}").unwrap();
 
     // Full match
     assert!(r.check("123"));
@@ -10,5 +10,29 @@ fn main() {
     assert!(r.has_match("abc 123 def"));
 
     // Search
-    assert_eq!(r.search("abc 123 def"), Some((4, 7)));
+    let m = r.search("abc 123 def").unwrap();
+    assert_eq!(m.range(), 4..7);
+    assert_eq!(m.as_str(), "123");
+
+    // All matches, an empty match right after a match is skipped
+    let all: Vec<_> = r
+        .find_iter("1, 22, 333, 4444")
+        .map(|m| m.as_str())
+        .collect();
+    assert_eq!(all, vec!["333", "4444"]);
+
+    // Capture groups
+    let r = Regex::new("([0-9]+)-([0-9]+)").unwrap();
+    let caps = r.captures("from 10-20").unwrap();
+    assert_eq!(caps.get(2).unwrap().as_str(), "20");
+
+    // Replacement, with a template or a closure
+    assert_eq!(r.replace_all("10-20, 3-4", "$2-$1"), "20-10, 4-3");
+
+    // Options
+    let r = RegexBuilder::new("abc")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    assert!(r.check("ABC"));
 }
@@ -1,71 +1,330 @@
 use std::fmt::Display;
 
-use crate::graph::Graph;
+use crate::dfa::Dfa;
+use crate::error::ErrorKind;
+use crate::find::MatchKind;
+use crate::graph::{Graph, Step};
+use crate::lazy::{LazyDfa, Mode};
+use crate::look::Look;
+use crate::serialize::{DeserializeError, Reader, Writer, read_graph, write_graph};
 use crate::set::Set as State;
+use crate::utf8::byte_graph;
 use crate::value::Value;
 
-fn update_value(graph: &Graph<Value>, input: &State, value: char, output: &mut State) {
+// Chars either side of a position, none inside the UTF-8 of a char
+type At = Option<(Option<char>, Option<char>)>;
+
+pub trait Input: Copy {
+    // Text consumed one unit at a time, chars of a `str` or bytes as chars up to `\xFF`
+
+    fn unit(self, index: usize) -> Option<(char, usize)>;
+
+    // Chars either side of byte `index`, which assertions look at.
+    // Matches only start between chars, and no assertion holds inside one
+    fn around(self, index: usize) -> At;
+}
+
+impl Input for &str {
+    fn unit(self, index: usize) -> Option<(char, usize)> {
+        self[index..].chars().next().map(|c| (c, c.len_utf8()))
+    }
+
+    fn around(self, index: usize) -> At {
+        Some((
+            self[..index].chars().next_back(),
+            self[index..].chars().next(),
+        ))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Bytes<'a> {
+    // Text that needn't be valid UTF-8. Raw patterns, outside Unicode mode
+    // somewhere, also start matches and hold assertions inside the UTF-8 of a char
+    pub text: &'a [u8],
+    pub raw: bool,
+}
+
+impl Input for Bytes<'_> {
+    fn unit(self, index: usize) -> Option<(char, usize)> {
+        self.text.get(index).map(|&x| (x as char, 1))
+    }
+
+    fn around(self, index: usize) -> At {
+        // Bytes not part of a valid char are U+FFFD, which no assertion treats specially
+        let text = self.text;
+        let decode = |x: &[u8]| std::str::from_utf8(x).ok()?.chars().next();
+
+        // Inside a char if one starting up to 3 bytes back is longer than that
+        let inside = (1..=index.min(3)).any(|x| {
+            let window = &text[index - x..text.len().min(index - x + 4)];
+            let chunk = window.utf8_chunks().next();
+
+            chunk
+                .and_then(|c| c.valid().chars().next())
+                .is_some_and(|c| c.len_utf8() > x)
+        });
+
+        if inside && !self.raw {
+            return None;
+        }
+
+        let prev = (index > 0).then(|| {
+            (1..=index.min(4))
+                .find_map(|x| decode(&text[index - x..index]))
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+        });
+
+        let next = (index < text.len()).then(|| {
+            (1..=(text.len() - index).min(4))
+                .find_map(|x| decode(&text[index..index + x]))
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+        });
+
+        Some((prev, next))
+    }
+}
+
+fn holds(look: Look, at: At) -> bool {
+    look.is_empty() || at.is_some_and(|(prev, next)| look.matches(prev, next))
+}
+
+fn takes(step: &Step<Value>, at: At, value: char) -> bool {
+    // Checks the step consumes value, between the chars `at`
+    step.value.as_ref().is_some_and(|x| x.matches(value)) && holds(step.look, at)
+}
+
+fn update_value(
+    graph: &Graph<Step<Value>>,
+    input: &State,
+    at: At,
+    value: char,
+    output: &mut State,
+) {
     // Update state by consuming value.
     for &node in &input.usizes {
-        for (next_value, next_node) in &graph.nodes[node].edges {
-            if next_value.matches(value) {
-                output.insert(*next_node);
+        for (step, next_node) in graph.edges(node) {
+            if takes(step, at, value) {
+                output.insert(next_node);
             }
         }
     }
 }
 
 struct TrackedState {
+    // Nodes in priority order, with the start of the thread at each
     state: State,
     indexes: Vec<usize>,
 }
 
 impl TrackedState {
     pub fn insert(&mut self, node: usize, index: usize) {
+        // The first thread to reach a node has the highest priority
+        if self.state.contains(node) {
+            return;
+        }
+
         self.state.insert(node);
-        self.indexes[node] = self.indexes[node].min(index);
+        self.indexes[node] = index;
     }
 
     pub fn clear(&mut self) {
         self.state.clear();
-        self.indexes.fill(usize::MAX);
     }
 }
 
 fn update_value_tracked(
-    graph: &Graph<Value>,
+    graph: &Graph<Step<Value>>,
     input: &TrackedState,
-    value: char,
+    at: At,
+    unit: Option<char>,
+    kind: MatchKind,
     output: &mut TrackedState,
-) {
-    // Update state by consuming value.
+) -> Option<usize> {
+    // Update state by consuming `unit`, between the chars `at`, in priority order.
+    // Returns the start of the first thread to accept, which is also the earliest.
+    // Lower priority threads are dropped unless looking for the longest match.
+    let mut found = None;
+
     for &node in &input.state.usizes {
-        for (next_value, next_node) in &graph.nodes[node].edges {
-            if next_value.matches(value) {
-                output.insert(*next_node, input.indexes[node]);
+        for (step, next_node) in graph.edges(node) {
+            match (&step.value, unit) {
+                (None, _) if holds(step.look, at) => {
+                    found = found.or(Some(input.indexes[node]));
+
+                    if kind == MatchKind::LeftmostFirst {
+                        return found;
+                    }
+                }
+                (Some(_), Some(c)) if takes(step, at, c) => {
+                    output.insert(next_node, input.indexes[node]);
+                }
+                _ => (),
             }
         }
     }
+
+    found
+}
+
+struct CapturedState {
+    // Each node keeps one thread, like a Pike VM.
+    // Slots of node `i` are in `slots[i * width..(i + 1) * width]`
+    state: State,
+    slots: Vec<Option<usize>>,
+    width: usize,
+}
+
+impl CapturedState {
+    fn get(&self, node: usize) -> &[Option<usize>] {
+        &self.slots[node * self.width..(node + 1) * self.width]
+    }
+
+    fn insert(&mut self, node: usize, slots: &[Option<usize>], saves: &[usize], index: usize) {
+        // The first thread to reach a node has the highest priority
+        if self.state.contains(node) {
+            return;
+        }
+
+        self.state.insert(node);
+
+        let out = &mut self.slots[node * self.width..(node + 1) * self.width];
+        out.copy_from_slice(slots);
+
+        for &slot in saves {
+            out[slot] = Some(index);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.state.clear();
+    }
+}
+
+fn update_value_captured(
+    graph: &Graph<Step<Value>>,
+    input: &CapturedState,
+    at: At,
+    unit: Option<char>,
+    index: usize,
+    kind: MatchKind,
+    output: &mut CapturedState,
+) -> Option<Vec<Option<usize>>> {
+    // Same as `update_value_tracked`, saving slots at byte `index`.
+    let mut found = None;
+
+    for &node in &input.state.usizes {
+        for (step, next_node) in graph.edges(node) {
+            match (&step.value, unit) {
+                (None, _) if holds(step.look, at) => {
+                    if found.is_none() {
+                        let mut slots = input.get(node).to_vec();
+
+                        for &slot in &step.saves {
+                            slots[slot] = Some(index);
+                        }
+
+                        found = Some(slots);
+                    }
+
+                    if kind == MatchKind::LeftmostFirst {
+                        return found;
+                    }
+                }
+                (Some(_), Some(c)) if takes(step, at, c) => {
+                    output.insert(next_node, input.get(node), &step.saves, index);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    found
 }
 
 #[derive(Debug)]
 pub struct Nfa {
-    graph: Graph<Value>,
+    graph: Graph<Step<Value>>,
     start: usize,
-    ends: Vec<usize>,
+    end: usize,
+    slots: usize,
+    kind: MatchKind,
+
+    // Finds whether and where matches end, falling back to the NFA.
+    // Only for matching chars
+    lazy: Option<LazyDfa>,
 }
 
 impl Display for Nfa {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "{}", self.graph)?;
         writeln!(f, "start: {}", self.start)?;
-        write!(f, "end: {:?}", self.ends)
+        write!(f, "end: {}", self.end)
     }
 }
 
 impl Nfa {
-    pub fn new(graph: Graph<Value>, start: usize, ends: Vec<usize>) -> Self {
-        Nfa { graph, start, ends }
+    pub fn new(
+        graph: Graph<Step<Value>>,
+        start: usize,
+        end: usize,
+        slots: usize,
+        kind: MatchKind,
+    ) -> Self {
+        Nfa {
+            lazy: Some(LazyDfa::new(&graph)),
+            graph,
+            start,
+            end,
+            slots,
+            kind,
+        }
+    }
+
+    pub fn write(&self, w: &mut Writer) {
+        w.usize(self.slots);
+        w.u32(self.kind as u32);
+        write_graph(w, &self.graph);
+        w.usize(self.start);
+        w.usize(self.end);
+    }
+
+    pub fn read(r: &mut Reader) -> Result<Self, DeserializeError> {
+        // Checks everything matching relies on, so corrupt data can't panic later
+        let slots = r.usize()?;
+
+        let kind = match r.u32()? {
+            0 => MatchKind::LeftmostFirst,
+            1 => MatchKind::LeftmostLongest,
+            _ => return Err(DeserializeError::Invalid("unknown match kind")),
+        };
+
+        let graph = read_graph(r, slots)?;
+        let start = r.index(graph.len())?;
+        let end = r.index(graph.len())?;
+
+        Ok(Self::new(graph, start, end, slots, kind))
+    }
+
+    pub fn to_byte_nfa(&self, size_limit: usize) -> Result<Self, ErrorKind> {
+        // Matches UTF-8 bytes instead of chars, see `byte_graph`
+        Ok(Nfa {
+            graph: byte_graph(&self.graph, size_limit)?,
+            start: self.start,
+            end: self.end,
+            slots: self.slots,
+            kind: self.kind,
+            lazy: None,
+        })
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    #[cfg(test)]
+    pub fn parts(&self) -> (&Graph<Step<Value>>, usize) {
+        (&self.graph, self.start)
     }
 
     fn create_state(&self) -> State {
@@ -79,45 +338,96 @@ impl Nfa {
         }
     }
 
+    fn create_captured_state(&self) -> CapturedState {
+        CapturedState {
+            state: self.create_state(),
+            slots: vec![None; self.graph.len() * self.slots],
+            width: self.slots,
+        }
+    }
+
+    fn accept(&self, node: usize, at: At) -> Option<&Step<Value>> {
+        // Transition into the end state between the chars `at`, if any
+        self.graph
+            .edges(node)
+            .map(|x| x.0)
+            .find(|x| x.value.is_none() && holds(x.look, at))
+    }
+
+    fn accepts(&self, state: &State, at: At) -> bool {
+        state.usizes.iter().any(|x| self.accept(*x, at).is_some())
+    }
+
+    fn lazy(&self, inp: &str, from: usize, mode: Mode) -> Option<Option<usize>> {
+        // End found by the lazy DFA, none if it gave up
+        let lazy = self.lazy.as_ref()?;
+        lazy.run(&self.graph, self.start, inp, from, mode)
+    }
+
     pub fn check(&self, inp: &str) -> bool {
         // Checks for full match.
+        match self.lazy(inp, 0, Mode::Anchored) {
+            Some(end) => end.is_some(),
+            None => self.check_nfa(inp),
+        }
+    }
+
+    pub fn check_nfa(&self, inp: impl Input) -> bool {
         let mut state = &mut self.create_state();
         let mut state2 = &mut self.create_state();
 
         state.insert(self.start);
 
-        for c in inp.chars() {
+        let mut index = 0;
+
+        while let Some((c, len)) = inp.unit(index) {
             if state.usizes.is_empty() {
                 return false;
             }
 
-            update_value(&self.graph, state, c, state2);
+            update_value(&self.graph, state, inp.around(index), c, state2);
             state.clear();
             (state, state2) = (state2, state);
+
+            index += len;
         }
 
-        self.ends.iter().any(|x| state.contains(*x))
+        self.accepts(state, inp.around(index))
     }
 
     pub fn has_match(&self, inp: &str) -> bool {
         // Checks for partial match.
+        match self.lazy(inp, 0, Mode::Unanchored) {
+            Some(end) => end.is_some(),
+            None => self.has_match_nfa(inp),
+        }
+    }
+
+    pub fn has_match_nfa(&self, inp: impl Input) -> bool {
         let mut state = &mut self.create_state();
         let mut state2 = &mut self.create_state();
 
         state.insert(self.start);
 
-        if self.ends.iter().any(|x| state.contains(*x)) {
+        if self.accepts(state, inp.around(0)) {
             return true;
         }
 
-        for c in inp.chars() {
-            update_value(&self.graph, state, c, state2);
+        let mut index = 0;
+
+        while let Some((c, len)) = inp.unit(index) {
+            update_value(&self.graph, state, inp.around(index), c, state2);
             state.clear();
             (state, state2) = (state2, state);
 
-            state.insert(self.start);
+            index += len;
+            let at = inp.around(index);
 
-            if self.ends.iter().any(|x| state.contains(*x)) {
+            if at.is_some() {
+                state.insert(self.start);
+            }
+
+            if self.accepts(state, at) {
                 return true;
             }
         }
@@ -125,43 +435,121 @@ impl Nfa {
         false
     }
 
-    pub fn search(&self, inp: &str) -> Option<(usize, usize)> {
-        // Finds earliest longest match.
+    pub fn search(&self, inp: &str, from: usize) -> Option<(usize, usize)> {
+        // Finds the leftmost match starting at or after byte `from`.
+        // Among those, the winner depends on the match kind.
+        // The lazy DFA finds the end of a leftmost-first match and a byte no
+        // later than its start, so the NFA only runs between the two.
+        let lazy = self.lazy.as_ref();
+
+        let (from, until) = match self.kind {
+            MatchKind::LeftmostFirst => {
+                match lazy.and_then(|x| x.leftmost(&self.graph, self.start, inp, from)) {
+                    Some(Some(bounds)) => bounds,
+                    Some(None) => return None,
+                    None => (from, inp.len()),
+                }
+            }
+            MatchKind::LeftmostLongest => (from, inp.len()),
+        };
+
+        self.search_nfa(inp, from, until)
+    }
+
+    pub fn search_nfa(&self, inp: impl Input, from: usize, until: usize) -> Option<(usize, usize)> {
+        // Same as `search`, stopping at byte `until`
         let mut state = &mut self.create_tracked_state();
         let mut state2 = &mut self.create_tracked_state();
 
-        state.insert(self.start, 0);
+        let mut best = None;
+        let mut index = from;
 
-        let found = |state: &TrackedState| {
-            self.ends
-                .iter()
-                .copied()
-                .filter(|x| state.state.contains(*x))
-                .map(|x| state.indexes[x])
-                .min()
-        };
+        loop {
+            // Assertions at `from` look at the text before it
+            let unit = inp.unit(index);
+            let at = inp.around(index);
 
-        let mut best = if found(state).is_some() {
-            Some((0, 0))
-        } else {
-            None
-        };
+            // Later starts have the lowest priority, and stop after a match
+            if best.is_none() && at.is_some() {
+                state.insert(self.start, index);
+            }
+
+            if state.state.usizes.is_empty() && best.is_some() {
+                break;
+            }
+
+            let found =
+                update_value_tracked(&self.graph, state, at, unit.map(|x| x.0), self.kind, state2);
+
+            // Later matches of threads with an earlier or same start win,
+            // only higher priority threads are left after a leftmost-first match
+            if let Some(start) = found {
+                best = match best {
+                    Some((x, _)) if x < start => best,
+                    _ => Some((start, index)),
+                };
+            }
 
-        for (index, c) in inp.chars().enumerate() {
-            update_value_tracked(&self.graph, state, c, state2);
             state.clear();
             (state, state2) = (state2, state);
 
-            if best.is_none() {
-                state.insert(self.start, index + 1);
+            let Some((_, len)) = unit else {
+                break;
+            };
+
+            if index == until {
+                break;
             }
 
-            if let Some(start) = found(state) {
-                best = match best {
-                    Some((x, _)) if x < start => best,
-                    _ => Some((start, index + 1)),
-                }
+            index += len;
+        }
+
+        best
+    }
+
+    pub fn to_dfa(&self, limit: usize) -> Result<Dfa, ErrorKind> {
+        Dfa::new(&self.graph, self.start, limit)
+    }
+
+    pub fn captures(&self, inp: impl Input, from: usize) -> Option<Vec<Option<usize>>> {
+        // Finds the same match as `search`, recording group positions.
+        let mut state = &mut self.create_captured_state();
+        let mut state2 = &mut self.create_captured_state();
+
+        let empty = vec![None; self.slots];
+        let mut best = None;
+
+        let mut index = from;
+
+        loop {
+            let unit = inp.unit(index);
+            let at = inp.around(index);
+
+            if best.is_none() && at.is_some() {
+                state.insert(self.start, &empty, &[], index);
+            }
+
+            if state.state.usizes.is_empty() && best.is_some() {
+                break;
             }
+            let c = unit.map(|x| x.0);
+
+            let found = update_value_captured(&self.graph, state, at, c, index, self.kind, state2);
+
+            if let Some(slots) = found
+                && best.as_ref().is_none_or(|x: &Vec<_>| slots[0] <= x[0])
+            {
+                best = Some(slots);
+            }
+
+            state.clear();
+            (state, state2) = (state2, state);
+
+            let Some((_, len)) = unit else {
+                break;
+            };
+
+            index += len;
         }
 
         best
@@ -1,192 +1,564 @@
 use std::{iter::Peekable, slice::Iter};
 
-use crate::token::Token;
-use Token::*;
+use crate::error::{ErrorKind, RegexError};
+use ErrorKind::*;
+
+use crate::flags::Flags;
+use crate::look::Look;
+
+use crate::token::{Span, Token, TokenKind};
+use TokenKind::*;
 
 use crate::ast::*;
-use UnOp::Range;
+use UnOp::{Lazy, Range};
 
-use crate::value::Value;
+use crate::unicode::{perl_class, posix_class, property_class};
+use crate::value::{Class, Value};
 
-type P<'a> = Peekable<Iter<'a, Token>>;
+struct Parser<'a> {
+    tokens: Peekable<Iter<'a, Token>>,
 
-fn parse_union(tokens: &mut P) -> Result<Ast, ()> {
-    let mut out = parse_concat(tokens)?;
+    // Offset of the end of the pattern, for errors at the end
+    end: usize,
 
-    while tokens.peek() == Some(&&Syntax(b'|')) {
-        tokens.next(); // Progress after peek
+    // Names of capture groups opened so far
+    names: Vec<String>,
 
-        let new = parse_concat(tokens)?;
-        out = union(out, new);
-    }
+    // Number of capture groups opened so far
+    groups: usize,
 
-    Ok(out)
+    // Flags in effect at the current token
+    flags: Flags,
+
+    // Groups and classes open around the current token
+    depth: u32,
+    nest_limit: u32,
 }
 
-fn parse_concat(tokens: &mut P) -> Result<Ast, ()> {
-    let mut out = parse_quantifier(tokens)?;
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<&'a TokenKind> {
+        self.tokens.peek().map(|x| &x.kind)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        self.tokens.next()
+    }
+
+    fn span(&mut self) -> Span {
+        // Span of the next token
+        match self.tokens.peek() {
+            Some(x) => x.span,
+            None => Span::new(self.end, self.end),
+        }
+    }
+
+    fn found(&mut self) -> Option<char> {
+        // Describes the next token for errors
+        match self.peek() {
+            Some(Literal(c)) => Some(*c),
+            Some(Syntax(c)) => Some(*c as char),
+            Some(Escape(_) | Property(..)) => Some('\\'),
+            Some(Posix(..)) => Some('['),
+            Some(ClassOp(c)) => Some(*c),
+            Some(NamedGroup(_) | NonCapture(_) | SetFlags(_)) => Some('('),
+            None => None,
+        }
+    }
 
-    while let Some(&token) = tokens.peek() {
-        // Otherwise need to impl backtracking
-        if *token == Syntax(b'|') || *token == Syntax(b')') {
-            break;
+    fn error<T>(&mut self, kind: ErrorKind) -> Result<T, RegexError> {
+        // Error at the next token
+        Err(RegexError::new(kind, self.span()))
+    }
+
+    fn nest(&mut self, open: Span) -> Result<(), RegexError> {
+        // Enters a group or class, bounding the recursion depth
+        if self.depth >= self.nest_limit {
+            return Err(RegexError::new(NestLimitExceeded(self.nest_limit), open));
         }
 
-        let new = parse_quantifier(tokens)?;
-        out = concat(out, new);
+        self.depth += 1;
+        Ok(())
     }
 
-    Ok(out)
-}
+    fn parse_union(&mut self) -> Result<Ast, RegexError> {
+        let mut out = self.parse_concat()?;
 
-fn parse_quantifier(tokens: &mut P) -> Result<Ast, ()> {
-    let out = parse_unit(tokens)?;
+        while self.peek() == Some(&Syntax(b'|')) {
+            self.next(); // Progress after peek
 
-    if let Some(Syntax(x)) = tokens.peek() {
-        if !b"?*+{".contains(x) {
-            return Ok(out);
+            let new = self.parse_concat()?;
+            out = union(out, new);
         }
 
-        tokens.next();
+        Ok(out)
+    }
 
-        let op = match x {
-            b'?' => Range(0, Some(1)),
-            b'*' => Range(0, None),
-            b'+' => Range(1, None),
+    fn parse_flags(&mut self) {
+        // Applies flags set for the rest of the group
+        while let Some(SetFlags(change)) = self.peek() {
+            self.flags = change.apply(self.flags);
+            self.next();
+        }
+    }
+
+    fn parse_concat(&mut self) -> Result<Ast, RegexError> {
+        self.parse_flags();
+        let mut out = self.parse_quantifier()?;
 
-            b'{' => {
-                let lower = parse_numeral(tokens)?;
+        loop {
+            self.parse_flags();
 
-                let upper = match tokens.next() {
-                    Some(Syntax(b'}')) => Some(lower),
-                    Some(Literal(',')) => {
-                        let upper = match tokens.peek() {
-                            Some(Literal(x)) if x.is_ascii_digit() => Some(parse_numeral(tokens)?),
-                            _ => None,
-                        };
+            // Otherwise need to impl backtracking
+            match self.peek() {
+                None | Some(Syntax(b'|' | b')')) => break,
+                _ => (),
+            }
+
+            let new = self.parse_quantifier()?;
+            out = concat(out, new);
+        }
 
-                        if tokens.next() != Some(&Syntax(b'}')) {
-                            return Err(());
+        Ok(out)
+    }
+
+    fn parse_quantifier(&mut self) -> Result<Ast, RegexError> {
+        let out = self.parse_unit()?;
+
+        if let Some(Syntax(x)) = self.peek() {
+            if !b"?*+{".contains(x) {
+                return Ok(out);
+            }
+
+            let open = self.next().unwrap().span;
+
+            let op = match x {
+                b'?' => Range(0, Some(1)),
+                b'*' => Range(0, None),
+                b'+' => Range(1, None),
+
+                b'{' => {
+                    let lower = self.parse_numeral()?;
+
+                    let upper = match self.peek() {
+                        Some(Syntax(b'}')) => Some(lower),
+                        Some(Literal(',')) => {
+                            self.next();
+
+                            let upper = match self.peek() {
+                                Some(Literal(x)) if x.is_ascii_digit() => {
+                                    Some(self.parse_numeral()?)
+                                }
+                                _ => None,
+                            };
+
+                            if self.peek() != Some(&Syntax(b'}')) {
+                                let found = self.found();
+                                return self.error(UnclosedRepetition(found));
+                            }
+
+                            upper
+                        }
+                        _ => {
+                            let found = self.found();
+                            return self.error(UnclosedRepetition(found));
                         }
+                    };
 
-                        upper
+                    let close = self.next().unwrap().span;
+
+                    if let Some(upper) = upper.filter(|x| *x < lower) {
+                        let span = Span::new(open.start, close.end);
+                        return Err(RegexError::new(InvalidRepetitionRange(lower, upper), span));
                     }
-                    _ => return Err(()),
+
+                    Range(lower, upper)
+                }
+
+                _ => unreachable!(),
+            };
+
+            // A trailing `?` prefers fewer iterations
+            let op = match op {
+                Range(lower, upper) if self.peek() == Some(&Syntax(b'?')) => {
+                    self.next();
+                    Lazy(lower, upper)
+                }
+                _ => op,
+            };
+
+            return Ok(unary(op, out));
+        }
+
+        Ok(out)
+    }
+
+    fn parse_unit(&mut self) -> Result<Ast, RegexError> {
+        let span = self.span();
+
+        let Some(token) = self.next() else {
+            return Err(RegexError::new(UnexpectedEnd, span));
+        };
+
+        let err = |kind| Err(RegexError::new(kind, span));
+
+        match &token.kind {
+            Literal(x) => {
+                // Escapes like `\xFF` are raw bytes outside Unicode mode, chars never are
+                let escaped = token.span.end - token.span.start > x.len_utf8();
+                let unicode = self.flags.contains(Flags::UNICODE);
+
+                let value = match escaped && !x.is_ascii() && !unicode {
+                    true => Value::class(&[(*x, *x)], false),
+                    false => Value::Char(*x),
                 };
 
-                Range(lower, upper)
+                Ok(self.symbol(value))
             }
+            Syntax(b'(') | NamedGroup(_) => {
+                let name = match &token.kind {
+                    NamedGroup(name) if self.names.contains(name) => {
+                        return err(DuplicateGroupName(name.clone()));
+                    }
+                    NamedGroup(name) => {
+                        self.names.push(name.clone());
+                        Some(name)
+                    }
+                    _ => None,
+                };
 
-            _ => unreachable!(),
-        };
+                // Numbered by opening bracket, so take the index before the inside
+                self.groups += 1;
+                let index = self.groups;
+
+                let out = self.parse_group(span, self.flags)?;
+
+                Ok(match name {
+                    Some(name) => named_group(index, name, out),
+                    None => group(index, out),
+                })
+            }
+            NonCapture(change) => self.parse_group(span, change.apply(self.flags)),
+            Syntax(x @ (b'^' | b'$')) => {
+                // Line boundaries in multi-line mode, otherwise text boundaries
+                let multi_line = self.flags.contains(Flags::MULTI_LINE);
+
+                Ok(Ast::Look(match (x, multi_line) {
+                    (b'^', true) => Look::START_LINE,
+                    (b'^', false) => Look::START_TEXT,
+                    (_, true) => Look::END_LINE,
+                    (_, false) => Look::END_TEXT,
+                }))
+            }
+            Syntax(b'.') => {
+                // Skips `symbol`, case folding doesn't change it
+                let spans: &[(char, char)] = if self.flags.contains(Flags::DOT_ALL) {
+                    &[]
+                } else if self.flags.contains(Flags::CRLF) {
+                    &[('\n', '\n'), ('\r', '\r')]
+                } else {
+                    &[('\n', '\n')]
+                };
 
-        return Ok(unary(op, out));
+                Ok(Ast::Sym(self.raw(Value::class(spans, true))))
+            }
+            // Already closed under case folding, so skips `symbol`
+            Escape(x @ ('d' | 'D' | 'w' | 'W' | 's' | 'S')) => {
+                Ok(Ast::Sym(self.raw(Value::Class(self.perl_class(*x)))))
+            }
+            Property(name, negated) => {
+                let class = self.property_class(name, *negated, span)?;
+                Ok(self.symbol(Value::Class(class)))
+            }
+            Escape('A') => Ok(Ast::Look(Look::START_TEXT)),
+            Escape('z') => Ok(Ast::Look(Look::END_TEXT)),
+            Escape(x @ ('b' | 'B')) => {
+                let unicode = self.flags.contains(Flags::UNICODE);
+
+                Ok(Ast::Look(match (x, unicode) {
+                    ('b', true) => Look::WORD,
+                    ('b', false) => Look::WORD_ASCII,
+                    (_, true) => Look::NOT_WORD,
+                    (_, false) => Look::NOT_WORD_ASCII,
+                }))
+            }
+            Syntax(b'[') => {
+                let class = self.parse_class(span)?;
+                Ok(self.symbol(Value::Class(class)))
+            }
+            Syntax(x @ (b'?' | b'*' | b'+' | b'{')) => err(NothingToRepeat(*x as char)),
+            Syntax(x @ (b'|' | b')')) => err(MissingExpression(*x as char)),
+            Syntax(b']') => err(UnmatchedBracket),
+            Syntax(b'}') => err(UnmatchedBrace),
+            Syntax(_) | Escape(_) | Posix(..) | ClassOp(_) | SetFlags(_) => unreachable!(),
+        }
     }
 
-    Ok(out)
-}
+    fn parse_group(&mut self, open: Span, flags: Flags) -> Result<Ast, RegexError> {
+        // Parses up to the closing bracket, flags only apply inside
+        self.nest(open)?;
 
-fn parse_unit(tokens: &mut P) -> Result<Ast, ()> {
-    match tokens.next() {
-        Some(Literal(x)) => Ok(Ast::Sym(Value::Char(*x))),
-        Some(Syntax(b'(')) => {
-            let out = parse_union(tokens)?;
+        let outer = self.flags;
+        self.flags = flags;
 
-            if tokens.next() != Some(&Syntax(b')')) {
-                return Err(());
-            }
+        let out = self.parse_union()?;
+        self.flags = outer;
+        self.depth -= 1;
 
-            Ok(out)
+        if self.peek() != Some(&Syntax(b')')) {
+            return Err(RegexError::new(UnclosedGroup, open));
         }
-        Some(Syntax(b'[')) => {
-            let inverse = tokens.peek() == Some(&&Literal('^'));
 
-            if inverse {
-                tokens.next();
-            }
+        self.next();
 
-            let mut spans = vec![parse_span(tokens)?];
-            while tokens.peek() != Some(&&Syntax(b']')) {
-                if let Ok(span) = parse_span(tokens) {
-                    spans.push(span);
-                } else {
-                    return Err(());
+        Ok(out)
+    }
+
+    fn symbol(&self, value: Value) -> Ast {
+        // Applies flags to a matched value
+        if self.flags.contains(Flags::CASE_INSENSITIVE) {
+            let unicode = self.flags.contains(Flags::UNICODE);
+            Ast::Sym(self.raw(value.case_fold(unicode)))
+        } else {
+            Ast::Sym(self.raw(value))
+        }
+    }
+
+    fn raw(&self, value: Value) -> Value {
+        // Classes outside Unicode mode match chars up to `\xFF` as raw bytes
+        match value {
+            Value::Class(x) if !self.flags.contains(Flags::UNICODE) => Value::Class(x.into_raw()),
+            x => x,
+        }
+    }
+
+    fn parse_class(&mut self, open: Span) -> Result<Class, RegexError> {
+        // Parses after `[` up to the matching `]`.
+        // Operators are left-associative and bind looser than listing items.
+        self.nest(open)?;
+
+        let inverse = self.peek() == Some(&Literal('^'));
+
+        if inverse {
+            self.next();
+        }
+
+        if self.peek() == Some(&Syntax(b']')) {
+            let close = self.span();
+            let span = Span::new(open.start, close.end);
+            return Err(RegexError::new(EmptyClass, span));
+        }
+
+        let mut out = self.parse_class_items(open)?;
+
+        while let Some(ClassOp(op)) = self.peek() {
+            self.next();
+            let other = self.parse_class_items(open)?;
+
+            out = match op {
+                '&' => out.intersection(&other),
+                '-' => out.difference(&other),
+                _ => out.symmetric_difference(&other),
+            };
+        }
+
+        self.next();
+        self.depth -= 1;
+
+        // Kept as `inverse`, so case folding happens before negating
+        Ok(Class::new(&out.positive(), inverse))
+    }
+
+    fn parse_class_items(&mut self, open: Span) -> Result<Class, RegexError> {
+        // Union of items up to an operator or `]`
+        let mut spans = vec![];
+
+        // Items can match nothing, as `[a&&b]` does, so emptiness is by the syntax
+        let mut empty = true;
+
+        loop {
+            match self.peek() {
+                None => return Err(RegexError::new(IncompleteClass, open)),
+                Some(Syntax(b']') | ClassOp(_)) if empty => {
+                    return self.error(EmptyClass);
+                }
+                Some(Syntax(b']') | ClassOp(_)) => break,
+                Some(Syntax(b'[')) => {
+                    let open = self.span();
+                    self.next();
+                    spans.extend(self.parse_class(open)?.positive());
                 }
+                Some(Escape(x)) => {
+                    spans.extend(self.perl_class(*x).positive());
+                    self.next();
+                }
+                Some(Property(name, negated)) => {
+                    let span = self.span();
+                    spans.extend(self.property_class(name, *negated, span)?.positive());
+                    self.next();
+                }
+                Some(Posix(name, negated)) => {
+                    let Some(posix) = posix_class(name) else {
+                        return self.error(UnknownPosixClass(name.clone()));
+                    };
+
+                    spans.extend(Class::new(posix, *negated).positive());
+                    self.next();
+                }
+                Some(Syntax(b'-')) if self.closes_after_dash() => {
+                    spans.push(('-', '-'));
+                    self.next();
+                }
+                _ => spans.push(self.parse_span()?),
             }
 
-            tokens.next();
+            empty = false;
+        }
+
+        // Operands are folded before operators, so `(?i)[a-z--A]` leaves out `a` too
+        let class = Class::new(&spans, false);
 
-            Ok(Ast::Sym(Value::class(&spans, inverse)))
+        if self.flags.contains(Flags::CASE_INSENSITIVE) {
+            Ok(class.case_fold(self.flags.contains(Flags::UNICODE)))
+        } else {
+            Ok(class)
         }
-        _ => Err(()),
     }
-}
 
-fn parse_span(tokens: &mut P) -> Result<(char, char), ()> {
-    let start = match tokens.peek() {
-        Some(Literal(c)) => *c,
-        _ => return Err(()),
-    };
+    fn closes_after_dash(&self) -> bool {
+        // A `-` right before `]` is literal, as in `[a-]`
+        let mut tokens = self.tokens.clone();
+        tokens.next().is_some_and(|x| x.kind == Syntax(b'-'))
+            && tokens.next().is_some_and(|x| x.kind == Syntax(b']'))
+    }
 
-    tokens.next();
+    fn perl_class(&self, name: char) -> Class {
+        // Class of `\d`, `\s` or `\w`, negated by an uppercase name
+        let unicode = self.flags.contains(Flags::UNICODE);
+        let spans = perl_class(name.to_ascii_lowercase(), unicode).unwrap();
 
-    let end = if tokens.peek() == Some(&&Syntax(b'-')) {
-        tokens.next();
+        Class::new(spans, name.is_ascii_uppercase())
+    }
 
-        match tokens.next() {
-            Some(Literal(c)) => *c,
-            _ => return Err(()),
+    fn property_class(&self, name: &str, negated: bool, span: Span) -> Result<Class, RegexError> {
+        // Class of `\p{name}`, negated by `\P{name}`
+        match property_class(name) {
+            Some(spans) => Ok(Class::new(spans, negated)),
+            None => Err(RegexError::new(UnknownProperty(name.to_string()), span)),
         }
-    } else {
-        start
-    };
-
-    if start > end {
-        Err(())
-    } else {
-        Ok((start, end))
     }
-}
 
-fn parse_numeral(tokens: &mut P) -> Result<u32, ()> {
-    let mut out = match tokens.next() {
-        Some(Literal(x)) => x.to_digit(10).ok_or(())?,
+    fn parse_span(&mut self) -> Result<(char, char), RegexError> {
+        let open = self.span();
 
-        _ => return Err(()),
-    };
+        let start = match self.peek() {
+            Some(Literal(c)) => *c,
+            _ => {
+                let found = self.found();
+                return self.error(IncompleteClassRange(found));
+            }
+        };
 
-    let f = |x: Option<&&Token>| {
-        x.and_then(|y| match y {
-            Literal(z) => z.to_digit(10),
-            _ => None,
-        })
-    };
+        self.next();
+
+        let end = if self.peek() == Some(&Syntax(b'-')) && !self.closes_after_dash() {
+            self.next();
+
+            match self.peek() {
+                Some(Literal(c)) => *c,
+                _ => {
+                    let found = self.found();
+                    return self.error(IncompleteClassRange(found));
+                }
+            }
+        } else {
+            return Ok((start, start));
+        };
 
-    while let Some(x) = f(tokens.peek()) {
-        tokens.next();
-        out = out * 10 + x;
+        let close = self.next().unwrap().span;
 
-        // Arbitrary limit
-        if out > u16::MAX as u32 {
-            return Err(());
+        if start > end {
+            let span = Span::new(open.start, close.end);
+            Err(RegexError::new(InvalidClassRange(start, end), span))
+        } else {
+            Ok((start, end))
         }
     }
 
-    Ok(out)
+    fn parse_numeral(&mut self) -> Result<u32, RegexError> {
+        let open = self.span();
+
+        let mut out = match self.peek() {
+            Some(Literal(x)) if x.is_ascii_digit() => x.to_digit(10).unwrap(),
+
+            _ => {
+                let found = self.found();
+                return self.error(InvalidRepetition(found));
+            }
+        };
+
+        self.next();
+
+        let f = |x: Option<&TokenKind>| {
+            x.and_then(|y| match y {
+                Literal(z) => z.to_digit(10),
+                _ => None,
+            })
+        };
+
+        while let Some(x) = f(self.peek()) {
+            let close = self.next().unwrap().span;
+            out = out * 10 + x;
+
+            // Arbitrary limit
+            if out > u16::MAX as u32 {
+                let span = Span::new(open.start, close.end);
+                return Err(RegexError::new(RepetitionTooLarge, span));
+            }
+        }
+
+        Ok(out)
+    }
 }
 
-pub fn parse(tokens: &[Token]) -> Result<Ast, &'static str> {
-    parse_union(&mut tokens.iter().peekable()).map_err(|()| "Invalid syntax")
+// Deep enough for any sensible pattern, shallow enough not to overflow the stack
+pub const DEFAULT_NEST_LIMIT: u32 = 250;
+
+pub fn parse(tokens: &[Token], flags: Flags, nest_limit: u32) -> Result<Ast, RegexError> {
+    // Parses with `flags` in effect from the start, as set by a builder
+    let mut parser = Parser {
+        tokens: tokens.iter().peekable(),
+        end: tokens.last().map_or(0, |x| x.span.end),
+        names: vec![],
+        groups: 0,
+        flags,
+        depth: 0,
+        nest_limit,
+    };
+
+    let out = parser.parse_union()?;
+
+    // `parse_union` only stops early on an unmatched `)`
+    match parser.peek() {
+        None => Ok(out),
+        Some(_) => parser.error(UnmatchedParen),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::flags::FlagChange;
 
-    use Literal as l;
+    fn l(a: char) -> Token {
+        Token::new(Literal(a), Span::default())
+    }
 
     fn s(a: char) -> Token {
-        Syntax(a as u8)
+        Token::new(Syntax(a as u8), Span::default())
+    }
+
+    fn kind(ast: Result<Ast, RegexError>) -> Result<Ast, ErrorKind> {
+        ast.map_err(|e| e.kind().clone())
     }
 
     fn char(a: char) -> Ast {
@@ -200,7 +572,7 @@ mod tests {
     #[test]
     fn single_letter() {
         let tokens = vec![l('a')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(char('a'));
         assert_eq!(ast, expected);
@@ -209,7 +581,7 @@ mod tests {
     #[test]
     fn exact_range() {
         let tokens = vec![l('a'), s('{'), l('5'), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(5, Some(5)), char('a')));
         assert_eq!(ast, expected);
@@ -218,7 +590,7 @@ mod tests {
     #[test]
     fn at_least_range() {
         let tokens = vec![l('a'), s('{'), l('1'), l('2'), l(','), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(12, None), char('a')));
         assert_eq!(ast, expected);
@@ -236,7 +608,7 @@ mod tests {
             l('4'),
             s('}'),
         ];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(12, Some(34)), char('a')));
         assert_eq!(ast, expected);
@@ -254,7 +626,7 @@ mod tests {
             l('z'),
             s(']'),
         ];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let spans = vec![('A', 'Z'), ('a', 'z')];
         let expected = Ok(class(&spans, false));
@@ -264,7 +636,7 @@ mod tests {
     #[test]
     fn escaped_single_class() {
         let tokens = vec![s('['), l('['), l('-'), l(']'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let spans = vec![('[', '['), ('-', '-'), (']', ']')];
         let expected = Ok(class(&spans, false));
@@ -274,7 +646,7 @@ mod tests {
     #[test]
     fn inverse_class() {
         let tokens = vec![s('['), l('^'), l('a'), s('-'), l('z'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let spans = vec![('a', 'z')];
         let expected = Ok(class(&spans, true));
@@ -284,7 +656,7 @@ mod tests {
     #[test]
     fn question_op() {
         let tokens = vec![l('a'), s('?')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(0, Some(1)), char('a')));
         assert_eq!(ast, expected);
@@ -293,7 +665,7 @@ mod tests {
     #[test]
     fn star_op() {
         let tokens = vec![l('a'), s('*')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(0, None), char('a')));
         assert_eq!(ast, expected);
@@ -302,16 +674,37 @@ mod tests {
     #[test]
     fn plus_op() {
         let tokens = vec![l('a'), s('+')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(unary(Range(1, None), char('a')));
         assert_eq!(ast, expected);
     }
 
+    #[test]
+    fn lazy_op() {
+        let tokens = vec![
+            l('a'),
+            s('*'),
+            s('?'),
+            l('b'),
+            s('{'),
+            l('2'),
+            s('}'),
+            s('?'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(
+            unary(Lazy(0, None), char('a')),
+            unary(Lazy(2, Some(2)), char('b')),
+        ));
+        assert_eq!(ast, expected);
+    }
+
     #[test]
     fn union_op() {
         let tokens = vec![l('a'), s('|'), l('b')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(union(char('a'), char('b')));
         assert_eq!(ast, expected);
@@ -320,7 +713,7 @@ mod tests {
     #[test]
     fn concat_op() {
         let tokens = vec![l('a'), l('b'), l('c')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(concat(concat(char('a'), char('b')), char('c')));
         assert_eq!(ast, expected);
@@ -329,7 +722,7 @@ mod tests {
     #[test]
     fn precendence() {
         let tokens = vec![l('a'), s('|'), l('b'), l('c')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(union(char('a'), concat(char('b'), char('c'))));
         assert_eq!(ast, expected);
@@ -347,11 +740,11 @@ mod tests {
             l('d'),
             s(')'),
         ];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
         let expected = Ok(concat(
             char('a'),
-            union(union(char('b'), char('c')), char('d')),
+            group(1, union(union(char('b'), char('c')), char('d'))),
         ));
         assert_eq!(ast, expected);
     }
@@ -369,135 +762,329 @@ mod tests {
             s(')'),
         ];
 
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
 
-        let expected = Ok(union(char('a'), concat(char('b'), char('c'))));
+        let expected = Ok(union(
+            char('a'),
+            group(1, group(2, concat(char('b'), char('c')))),
+        ));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn group_numbering() {
+        let tokens = vec![
+            s('('),
+            l('a'),
+            s('('),
+            l('b'),
+            s(')'),
+            s(')'),
+            s('('),
+            l('c'),
+            s(')'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(
+            group(1, concat(char('a'), group(2, char('b')))),
+            group(3, char('c')),
+        ));
         assert_eq!(ast, expected);
     }
 
     #[test]
     fn invalid_empty() {
         let tokens = vec![];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_empty_brackets() {
         let tokens = vec![s('('), s('('), s(')'), s(')')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_brackets() {
         let tokens = vec![s('('), s('('), l('a'), s(')')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_quantifier() {
         let tokens = vec![l('a'), s('|'), s('*')];
-        let ast = parse(&tokens);
-        assert!(ast.is_err());
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(NothingToRepeat('*')));
     }
 
     #[test]
     fn invalid_double_quantifier() {
         let tokens = vec![l('a'), s('*'), s('*')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_nonclosed_range() {
         let tokens = vec![l('a'), s('{')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_missing_range() {
         let tokens = vec![l('a'), s('{'), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_char_in_range() {
         let tokens = vec![l('a'), s('{'), l('a'), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_standalone_range() {
         let tokens = vec![s('{'), l('a'), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_missing_lower_range() {
         let tokens = vec![l('a'), s('{'), l(','), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_closing_range() {
         let tokens = vec![l('a'), s('}')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_nonclosed_class() {
         let tokens = vec![s('['), l('a')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_closing_class() {
         let tokens = vec![l('a'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
-    fn invalid_start_bounded_class() {
+    fn trailing_dash_class() {
         let tokens = vec![s('['), l('a'), s('-'), s(']')];
-        let ast = parse(&tokens);
-        assert!(ast.is_err());
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(class(&[('a', 'a'), ('-', '-')], false));
+        assert_eq!(ast, expected);
     }
 
     #[test]
     fn invalid_end_bounded_class() {
         let tokens = vec![s('['), s('-'), l('a'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_empty_class() {
         let tokens = vec![s('['), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_everything_class() {
         let tokens = vec![s('['), l('^'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
 
     #[test]
     fn invalid_reverse_class() {
         let tokens = vec![s('['), l('z'), s('-'), l('a'), s(']')];
-        let ast = parse(&tokens);
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
         assert!(ast.is_err());
     }
+
+    #[test]
+    fn invalid_unmatched_bracket() {
+        let tokens = vec![l('a'), s(')'), l('b')];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(UnmatchedParen));
+    }
+
+    #[test]
+    fn invalid_reverse_range() {
+        let tokens = vec![l('a'), s('{'), l('3'), l(','), l('1'), s('}')];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(InvalidRepetitionRange(3, 1)));
+    }
+
+    #[test]
+    fn invalid_large_range() {
+        let tokens = vec![
+            l('a'),
+            s('{'),
+            l('7'),
+            l('0'),
+            l('0'),
+            l('0'),
+            l('0'),
+            s('}'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(RepetitionTooLarge));
+    }
+
+    #[test]
+    fn named_groups() {
+        let tokens = vec![
+            Token::new(NamedGroup("x".to_string()), Span::default()),
+            l('a'),
+            s(')'),
+            s('('),
+            l('b'),
+            s(')'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(named_group(1, "x", char('a')), group(2, char('b'))));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn invalid_duplicate_name() {
+        let tokens = vec![
+            Token::new(NamedGroup("x".to_string()), Span::default()),
+            l('a'),
+            s(')'),
+            Token::new(NamedGroup("x".to_string()), Span::default()),
+            l('b'),
+            s(')'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(DuplicateGroupName("x".to_string())));
+    }
+
+    #[test]
+    fn non_capture_group() {
+        let tokens = vec![
+            Token::new(NonCapture(FlagChange::default()), Span::default()),
+            l('a'),
+            s(')'),
+            s('('),
+            l('b'),
+            s(')'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(char('a'), group(1, char('b'))));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn scoped_flags() {
+        let on = Flags::CASE_INSENSITIVE;
+        let change = FlagChange {
+            on,
+            ..Default::default()
+        };
+
+        let tokens = vec![
+            l('a'),
+            s('('),
+            Token::new(SetFlags(change), Span::default()),
+            l('b'),
+            s(')'),
+            l('c'),
+            Token::new(NonCapture(change), Span::default()),
+            l('d'),
+            s(')'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(
+            concat(
+                concat(char('a'), group(1, class(&[('B', 'B'), ('b', 'b')], false))),
+                char('c'),
+            ),
+            class(&[('D', 'D'), ('d', 'd')], false),
+        ));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn invalid_only_flags() {
+        let change = FlagChange::default();
+        let tokens = vec![Token::new(SetFlags(change), Span::default())];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+        assert_eq!(kind(ast), Err(UnexpectedEnd));
+    }
+
+    #[test]
+    fn anchors() {
+        let tokens = vec![s('^'), l('a'), s('$')];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(
+            concat(Ast::Look(Look::START_TEXT), char('a')),
+            Ast::Look(Look::END_TEXT),
+        ));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn dot() {
+        let change = FlagChange {
+            on: Flags::DOT_ALL,
+            ..Default::default()
+        };
+
+        let tokens = vec![
+            s('.'),
+            Token::new(SetFlags(change), Span::default()),
+            s('.'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(class(&[('\n', '\n')], true), class(&[], true)));
+        assert_eq!(ast, expected);
+    }
+
+    #[test]
+    fn multi_line_anchors() {
+        let change = FlagChange {
+            on: Flags::MULTI_LINE,
+            ..Default::default()
+        };
+
+        let tokens = vec![
+            Token::new(SetFlags(change), Span::default()),
+            s('^'),
+            Token::new(Escape('z'), Span::default()),
+            s('$'),
+        ];
+        let ast = parse(&tokens, Flags::UNICODE, DEFAULT_NEST_LIMIT);
+
+        let expected = Ok(concat(
+            concat(Ast::Look(Look::START_LINE), Ast::Look(Look::END_TEXT)),
+            Ast::Look(Look::END_LINE),
+        ));
+        assert_eq!(ast, expected);
+    }
 }
@@ -1,12 +1,26 @@
+use std::borrow::Cow;
 use std::fmt::Display;
+use std::sync::Arc;
 
-use crate::compiler::compile;
+use crate::builder::RegexBuilder;
+use crate::captures::{Captures, CapturesIter};
+use crate::compiler::{capture_names, compile};
+use crate::dfa::{DEFAULT_STATE_LIMIT, Dfa};
+use crate::error::RegexError;
+use crate::find::{FindIter, Match, MatchKind, Split, SplitN};
+use crate::flags::Flags;
 use crate::lexer::lexer;
 use crate::nfa::Nfa;
 use crate::parser::parse;
+use crate::replace::{Replacer, replacen};
+use crate::serialize::{DeserializeError, Kind, Reader, Writer};
+use crate::token::Span;
 
+#[derive(Debug)]
 pub struct Regex {
+    pattern: String,
     nfa: Nfa,
+    names: Arc<[Option<String>]>,
 }
 
 impl Display for Regex {
@@ -16,12 +30,96 @@ impl Display for Regex {
 }
 
 impl Regex {
-    pub fn new(pattern: &str) -> Self {
-        let tokens = lexer(pattern).unwrap();
-        let ast = parse(&tokens).unwrap();
-        let nfa = compile(&ast);
+    pub fn new(pattern: &str) -> Result<Self, RegexError> {
+        RegexBuilder::new(pattern).build()
+    }
+
+    pub(crate) fn with_options(
+        pattern: &str,
+        flags: Flags,
+        nest_limit: u32,
+        size_limit: usize,
+        kind: MatchKind,
+    ) -> Result<Self, RegexError> {
+        let tokens = lexer(pattern, flags).map_err(|e| e.with_pattern(pattern))?;
+        let ast = parse(&tokens, flags, nest_limit).map_err(|e| e.with_pattern(pattern))?;
+
+        // Too large as a whole, so the error covers the full pattern
+        let nfa = compile(&ast, size_limit, kind).map_err(|kind| {
+            RegexError::new(kind, Span::new(0, pattern.len())).with_pattern(pattern)
+        })?;
+
+        let names = capture_names(&ast).into();
+
+        Ok(Self {
+            pattern: pattern.to_string(),
+            nfa,
+            names,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        // Compiled form, which `from_bytes` loads without parsing or compiling again.
+        let mut w = Writer::new(Kind::Regex);
+        w.str(&self.pattern);
+        w.usize(self.names.len());
+
+        for name in self.names.iter() {
+            w.u32(u32::from(name.is_some()));
+            w.str(name.as_deref().unwrap_or(""));
+        }
+
+        self.nfa.write(&mut w);
+        w.finish()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        // Loads the output of `to_bytes`, written with either byte order.
+        // Corrupt data is an error rather than a regex that panics.
+        let mut r = Reader::new(bytes, Kind::Regex)?;
+        let pattern = r.str()?.to_string();
+
+        let names = (0..r.count(2)?)
+            .map(|_| {
+                let named = r.bool()?;
+                let name = r.str()?;
+                Ok(named.then(|| name.to_string()))
+            })
+            .collect::<Result<Arc<[_]>, _>>()?;
+
+        let nfa = Nfa::read(&mut r)?;
+        r.finish()?;
+
+        // The whole match is group 0
+        if names.is_empty() || nfa.slots() != 2 * names.len() {
+            return Err(DeserializeError::Invalid("group count doesn't match"));
+        }
+
+        Ok(Self {
+            pattern,
+            nfa,
+            names,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        // The pattern this was built from
+        &self.pattern
+    }
+
+    pub fn to_dfa(&self) -> Result<Dfa, RegexError> {
+        // Builds a full DFA up front, see `to_dfa_with_limit`.
+        self.to_dfa_with_limit(DEFAULT_STATE_LIMIT)
+    }
 
-        Self { nfa }
+    pub fn to_dfa_with_limit(&self, limit: usize) -> Result<Dfa, RegexError> {
+        // Builds a minimal DFA for `check` and `has_match`, erroring if building
+        // takes more than `limit` states, or more work than that many small ones.
+        // Some patterns, like `(a|b)*a(a|b){20}`, need exponentially many states,
+        // which the lazy DFA of `Regex` avoids.
+        self.nfa.to_dfa(limit).map_err(|kind| {
+            RegexError::new(kind, Span::new(0, self.pattern.len())).with_pattern(&self.pattern)
+        })
     }
 
     pub fn check(&self, text: &str) -> bool {
@@ -34,8 +132,61 @@ impl Regex {
         self.nfa.has_match(text)
     }
 
-    pub fn search(&self, text: &str) -> Option<(usize, usize)> {
-        // Finds the earliest longest match, treating quantifiers as greedy.
-        self.nfa.search(text)
+    pub fn search<'t>(&self, text: &'t str) -> Option<Match<'t>> {
+        // Finds the leftmost match, by default preferring earlier alternatives and greedy
+        // quantifiers over lazy ones. See `MatchKind` for the longest match instead.
+        let (start, end) = self.nfa.search(text, 0)?;
+        Some(Match::new(text, start, end))
+    }
+
+    pub fn find_iter<'r, 't>(&'r self, text: &'t str) -> FindIter<'r, 't> {
+        // Finds successive non-overlapping matches, using the same rule as `search`.
+        FindIter::new(&self.nfa, text)
+    }
+
+    pub fn split<'r, 't>(&'r self, text: &'t str) -> Split<'r, 't> {
+        // Text between the matches of `find_iter`, as byte slices of `text`.
+        // Matches at either end give an empty first or last field,
+        // and a pattern matching empty text splits between every char.
+        Split::new(&self.nfa, text)
+    }
+
+    pub fn splitn<'r, 't>(&'r self, text: &'t str, limit: usize) -> SplitN<'r, 't> {
+        // Same as `split`, stopping at `limit` fields with the rest of the text as the last.
+        SplitN::new(&self.nfa, text, limit)
+    }
+
+    pub fn captures<'t>(&self, text: &'t str) -> Option<Captures<'t>> {
+        // Finds the same match as `search`, with the match of every group.
+        let slots = self.nfa.captures(text, 0)?;
+        Some(Captures::new(text, slots, self.names.clone()))
+    }
+
+    pub fn captures_iter<'r, 't>(&'r self, text: &'t str) -> CapturesIter<'r, 't> {
+        // Captures of each match `find_iter` finds.
+        CapturesIter::new(&self.nfa, text, self.names.clone())
+    }
+
+    pub fn replace<'t>(&self, text: &'t str, rep: impl Replacer) -> Cow<'t, str> {
+        // Replaces the first match, see `replacen`.
+        self.replacen(text, 1, rep)
+    }
+
+    pub fn replace_all<'t>(&self, text: &'t str, rep: impl Replacer) -> Cow<'t, str> {
+        // Replaces every match, see `replacen`.
+        self.replacen(text, 0, rep)
+    }
+
+    pub fn replacen<'t>(&self, text: &'t str, limit: usize, rep: impl Replacer) -> Cow<'t, str> {
+        // Replaces the first `limit` matches of `find_iter`, or all of them if `limit` is 0.
+        // `rep` is a template like `$1-${name}`, or a closure given the captures of each match.
+        // Borrows the text when nothing matches.
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        replacen(text, self.captures_iter(text).take(limit), rep)
+    }
+
+    pub fn capture_names(&self) -> impl ExactSizeIterator<Item = Option<&str>> {
+        // Name of each group by index, starting with the unnamed whole match.
+        self.names.iter().map(|x| x.as_deref())
     }
 }
@@ -0,0 +1,73 @@
+use std::borrow::Cow;
+
+use crate::captures::Captures;
+
+pub trait Replacer {
+    // Appends the replacement for one match to `dst`
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String);
+}
+
+impl Replacer for &str {
+    // Template with group references, see `Captures::expand`
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+impl Replacer for &String {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+impl Replacer for String {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+impl<F, T> Replacer for F
+where
+    F: FnMut(&Captures<'_>) -> T,
+    T: AsRef<str>,
+{
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        dst.push_str(self(caps).as_ref());
+    }
+}
+
+pub struct NoExpand<'s>(pub &'s str);
+
+impl Replacer for NoExpand<'_> {
+    // Inserted as is, `$` included
+    fn replace_append(&mut self, _: &Captures<'_>, dst: &mut String) {
+        dst.push_str(self.0);
+    }
+}
+
+pub(crate) fn replacen<'t>(
+    text: &'t str,
+    matches: impl Iterator<Item = Captures<'t>>,
+    mut rep: impl Replacer,
+) -> Cow<'t, str> {
+    // Replaces each match, borrowing the text if there are none
+    let mut matches = matches.peekable();
+
+    if matches.peek().is_none() {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+
+    for caps in matches {
+        let m = caps.get(0).unwrap();
+
+        out.push_str(&text[last..m.start()]);
+        rep.replace_append(&caps, &mut out);
+        last = m.end();
+    }
+
+    out.push_str(&text[last..]);
+    Cow::Owned(out)
+}
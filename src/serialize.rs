@@ -0,0 +1,361 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::graph::{Graph, Repeat, Step};
+use crate::look::Look;
+use crate::value::Value;
+
+// Format of serialised automata.
+//
+// A header of four words, the magic, an endian tag in the writer's byte order,
+// the version and the kind, followed by the data as words in the same order.
+// Everything is a whole number of words, so data read from an aligned buffer
+// stays aligned and tables can be used in place.
+const MAGIC: [u8; 4] = *b"RGXE";
+const ENDIAN: u32 = 0x0102_0304;
+const VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kind {
+    Regex = 1,
+    Dfa = 2,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeserializeError {
+    Truncated,
+    BadMagic,
+    UnsupportedVersion(u32),
+    UnknownKind(u32),
+    WrongKind,
+    ForeignEndian,
+    Unaligned,
+    Invalid(&'static str),
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use DeserializeError::*;
+
+        match self {
+            Truncated => write!(f, "unexpected end of data"),
+            BadMagic => write!(f, "not a serialised regex"),
+            UnsupportedVersion(x) => write!(f, "unsupported format version {x}"),
+            UnknownKind(x) => write!(f, "unknown automaton kind {x}"),
+            WrongKind => write!(f, "data holds a different kind of automaton"),
+            ForeignEndian => write!(f, "data was written with the other byte order"),
+            Unaligned => write!(f, "data is not aligned to 4 bytes"),
+            Invalid(x) => write!(f, "invalid data, {x}"),
+        }
+    }
+}
+
+impl Error for DeserializeError {}
+
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub fn new(kind: Kind) -> Self {
+        let mut w = Self { bytes: Vec::new() };
+
+        w.bytes.extend(MAGIC);
+        w.u32(ENDIAN);
+        w.u32(VERSION);
+        w.u32(kind as u32);
+
+        w
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn u32(&mut self, x: u32) {
+        self.bytes.extend(x.to_ne_bytes());
+    }
+
+    pub fn usize(&mut self, x: usize) {
+        // Automata larger than words can index aren't built
+        self.u32(u32::try_from(x).unwrap());
+    }
+
+    pub fn words(&mut self, xs: &[u32]) {
+        for &x in xs {
+            self.u32(x);
+        }
+    }
+
+    pub fn str(&mut self, x: &str) {
+        // Length, then the bytes padded to a whole word
+        self.usize(x.len());
+        self.bytes.extend(x.as_bytes());
+        self.bytes.resize(self.bytes.len().next_multiple_of(4), 0);
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+
+    // Written with the other byte order
+    swap: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8], kind: Kind) -> Result<Self, DeserializeError> {
+        if data.len() < 16 {
+            return Err(DeserializeError::Truncated);
+        }
+
+        if data[..4] != MAGIC {
+            return Err(DeserializeError::BadMagic);
+        }
+
+        let tag: [u8; 4] = data[4..8].try_into().unwrap();
+
+        let swap = match u32::from_ne_bytes(tag) {
+            ENDIAN => false,
+            x if x.swap_bytes() == ENDIAN => true,
+            _ => return Err(DeserializeError::BadMagic),
+        };
+
+        let mut r = Self { data, pos: 8, swap };
+
+        match r.u32()? {
+            VERSION => (),
+            x => return Err(DeserializeError::UnsupportedVersion(x)),
+        }
+
+        match r.u32()? {
+            x if x == kind as u32 => Ok(r),
+            x if x == Kind::Regex as u32 || x == Kind::Dfa as u32 => {
+                Err(DeserializeError::WrongKind)
+            }
+            x => Err(DeserializeError::UnknownKind(x)),
+        }
+    }
+
+    pub fn finish(self) -> Result<(), DeserializeError> {
+        match self.pos == self.data.len() {
+            true => Ok(()),
+            false => Err(DeserializeError::Invalid("trailing bytes")),
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DeserializeError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .ok_or(DeserializeError::Truncated)?;
+        let out = self
+            .data
+            .get(self.pos..end)
+            .ok_or(DeserializeError::Truncated)?;
+
+        self.pos = end;
+        Ok(out)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, DeserializeError> {
+        let x = u32::from_ne_bytes(self.bytes(4)?.try_into().unwrap());
+        Ok(if self.swap { x.swap_bytes() } else { x })
+    }
+
+    pub fn usize(&mut self) -> Result<usize, DeserializeError> {
+        Ok(self.u32()? as usize)
+    }
+
+    pub fn index(&mut self, len: usize) -> Result<usize, DeserializeError> {
+        // Index into something of length `len`
+        match self.usize()? {
+            x if x < len => Ok(x),
+            _ => Err(DeserializeError::Invalid("index out of range")),
+        }
+    }
+
+    pub fn bool(&mut self) -> Result<bool, DeserializeError> {
+        match self.u32()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(DeserializeError::Invalid("expected a boolean")),
+        }
+    }
+
+    pub fn char(&mut self) -> Result<char, DeserializeError> {
+        char::from_u32(self.u32()?).ok_or(DeserializeError::Invalid("invalid char"))
+    }
+
+    pub fn count(&mut self, words: usize) -> Result<usize, DeserializeError> {
+        // Number of items taking at least `words` each, checked against the data left
+        // so a corrupt count can't allocate too much
+        let count = self.usize()?;
+
+        match count.checked_mul(words * 4) {
+            Some(x) if x <= self.data.len() - self.pos => Ok(count),
+            _ => Err(DeserializeError::Truncated),
+        }
+    }
+
+    pub fn str(&mut self) -> Result<&'a str, DeserializeError> {
+        let len = self.count(0)?;
+        let bytes = self.bytes(len)?;
+        self.bytes(len.next_multiple_of(4) - len)?;
+
+        std::str::from_utf8(bytes).map_err(|_| DeserializeError::Invalid("invalid UTF-8"))
+    }
+
+    pub fn words(&mut self, len: usize) -> Result<&'a [u32], DeserializeError> {
+        // Words used in place, so they must be aligned and in this byte order
+        if self.swap {
+            return Err(DeserializeError::ForeignEndian);
+        }
+
+        let bytes = self.bytes(len.checked_mul(4).ok_or(DeserializeError::Truncated)?)?;
+
+        // SAFETY: any 4 bytes are a valid u32
+        let (prefix, words, _) = unsafe { bytes.align_to::<u32>() };
+
+        match prefix.is_empty() && words.len() == len {
+            true => Ok(words),
+            false => Err(DeserializeError::Unaligned),
+        }
+    }
+}
+
+fn write_step(w: &mut Writer, step: &Step<Value>) {
+    // Classes are written as the chars they match, so there is no inverse flag
+    match &step.value {
+        None => w.u32(0),
+        Some(Value::Char(x)) => {
+            w.u32(1);
+            w.u32(*x as u32);
+        }
+        Some(Value::Class(x)) => {
+            let spans = x.positive();
+
+            w.u32(2);
+            w.usize(spans.len());
+
+            for (start, end) in spans {
+                w.u32(start as u32);
+                w.u32(end as u32);
+            }
+        }
+    }
+
+    w.usize(step.saves.len());
+
+    for &slot in &step.saves {
+        w.usize(slot);
+    }
+
+    w.u32(step.look.bits().into());
+}
+
+fn read_step(r: &mut Reader, slots: usize) -> Result<Step<Value>, DeserializeError> {
+    let value = match r.u32()? {
+        0 => None,
+        1 => Some(Value::Char(r.char()?)),
+        2 => {
+            let spans = (0..r.count(2)?)
+                .map(|_| Ok((r.char()?, r.char()?)))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let sorted =
+                spans.iter().all(|x| x.0 <= x.1) && spans.windows(2).all(|x| x[0].1 < x[1].0);
+
+            if !sorted {
+                return Err(DeserializeError::Invalid("class spans out of order"));
+            }
+
+            Some(Value::class(&spans, false))
+        }
+        _ => return Err(DeserializeError::Invalid("unknown value")),
+    };
+
+    let saves = (0..r.count(1)?)
+        .map(|_| r.index(slots))
+        .collect::<Result<_, _>>()?;
+
+    let look = u16::try_from(r.u32()?)
+        .ok()
+        .and_then(Look::from_bits)
+        .ok_or(DeserializeError::Invalid("unknown assertion"))?;
+
+    Ok(Step { value, saves, look })
+}
+
+pub(crate) fn write_graph(w: &mut Writer, graph: &Graph<Step<Value>>) {
+    w.usize(graph.len());
+
+    for node in &graph.nodes {
+        w.usize(node.edges.len());
+
+        for (step, next) in &node.edges {
+            write_step(w, step);
+            w.usize(*next);
+        }
+    }
+
+    w.usize(graph.repeats.len());
+
+    for x in &graph.repeats {
+        w.usize(x.base);
+        w.usize(x.lower);
+        w.u32(u32::from(x.upper.is_some()));
+        w.usize(x.upper.unwrap_or(0));
+        w.u32(u32::from(x.greedy));
+        write_step(w, &x.label);
+        w.usize(x.exit);
+    }
+}
+
+pub(crate) fn read_graph(
+    r: &mut Reader,
+    slots: usize,
+) -> Result<Graph<Step<Value>>, DeserializeError> {
+    // Graph with every edge and counter state inside it
+    let mut graph = Graph::default();
+    let len = r.count(1)?;
+
+    for _ in 0..len {
+        let node = graph.new_node();
+
+        for _ in 0..r.count(4)? {
+            let step = read_step(r, slots)?;
+            let next = r.index(len)?;
+
+            graph.add_edge(node, next, step);
+        }
+    }
+
+    for _ in 0..r.count(8)? {
+        let base = r.index(len)?;
+        let lower = r.usize()?;
+        let bounded = r.bool()?;
+        let upper = bounded.then_some(r.usize()?);
+
+        let greedy = r.bool()?;
+        let label = read_step(r, slots)?;
+        let exit = r.index(len)?;
+
+        let last = base.checked_add(upper.unwrap_or(lower));
+
+        if upper.is_some_and(|x| x < lower) || last.is_none_or(|x| x >= len) {
+            return Err(DeserializeError::Invalid("counter states out of range"));
+        }
+
+        graph.add_repeat(Repeat {
+            base,
+            lower,
+            upper,
+            greedy,
+            label,
+            exit,
+        });
+    }
+
+    Ok(graph)
+}
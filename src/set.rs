@@ -1,3 +1,4 @@
+#[derive(Debug)]
 pub struct Set {
     // Set with efficient iter
     pub usizes: Vec<usize>,
@@ -26,7 +27,11 @@ impl Set {
     }
 
     pub fn clear(&mut self) {
-        self.bools.fill(false);
+        // Only resets inserted values, large sets are mostly empty
+        for &x in &self.usizes {
+            self.bools[x] = false;
+        }
+
         self.usizes.clear();
     }
 }
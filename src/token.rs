@@ -1,5 +1,49 @@
+use crate::flags::FlagChange;
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Span {
+    // Byte offsets into the pattern
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
 #[derive(Eq, PartialEq, Debug)]
-pub enum Token {
+pub enum TokenKind {
     Literal(char),
     Syntax(u8),
+
+    // Letter escape interpreted by the parser, such as `\A`
+    Escape(char),
+
+    // `\p{name}`, or negated `\P{name}`
+    Property(String, bool),
+
+    // `[:name:]`, or negated `[:^name:]`, inside a class
+    Posix(String, bool),
+
+    // `&&`, `--` or `~~` between class items, as the doubled char
+    ClassOp(char),
+    NamedGroup(String),
+
+    // `(?flags:` opens a group, `(?flags)` applies to the rest of the group
+    NonCapture(FlagChange),
+    SetFlags(FlagChange),
+}
+
+#[derive(Eq, PartialEq, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, span: Span) -> Self {
+        Self { kind, span }
+    }
 }
@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+
+use crate::unicode_tables::case_folding_simple::CASE_FOLDING_SIMPLE;
+use crate::unicode_tables::perl_decimal::DECIMAL_NUMBER;
+use crate::unicode_tables::perl_space::WHITE_SPACE;
+use crate::unicode_tables::perl_word::PERL_WORD;
+use crate::unicode_tables::property_values::PROPERTY_VALUES;
+use crate::unicode_tables::{general_category, script};
+
+const ASCII_DIGIT: &[(char, char)] = &[('0', '9')];
+const ASCII_SPACE: &[(char, char)] = &[('\t', '\r'), (' ', ' ')];
+const ASCII_WORD: &[(char, char)] = &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')];
+
+fn in_table(table: &[(char, char)], c: char) -> bool {
+    // Tables are sorted and disjoint
+    table
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                Ordering::Less
+            } else if start > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+pub fn is_word_char(c: char) -> bool {
+    // Word char as in `\w`, letters, marks, digits and connectors
+    if c.is_ascii() {
+        is_ascii_word_char(c)
+    } else {
+        in_table(PERL_WORD, c)
+    }
+}
+
+pub fn is_ascii_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+pub fn perl_class(name: char, unicode: bool) -> Option<&'static [(char, char)]> {
+    // Spans of `\d`, `\s` or `\w`
+    match (name, unicode) {
+        ('d', true) => Some(DECIMAL_NUMBER),
+        ('s', true) => Some(WHITE_SPACE),
+        ('w', true) => Some(PERL_WORD),
+        ('d', false) => Some(ASCII_DIGIT),
+        ('s', false) => Some(ASCII_SPACE),
+        ('w', false) => Some(ASCII_WORD),
+        _ => None,
+    }
+}
+
+pub fn case_variants(start: char, end: char) -> impl Iterator<Item = char> {
+    // Chars equal under simple case folding to a char in `start..=end`,
+    // only looking at chars with variants rather than every char
+    let from = CASE_FOLDING_SIMPLE.partition_point(|x| x.0 < start);
+
+    CASE_FOLDING_SIMPLE[from..]
+        .iter()
+        .take_while(move |x| x.0 <= end)
+        .flat_map(|x| x.1.iter().copied())
+}
+
+pub fn posix_class(name: &str) -> Option<&'static [(char, char)]> {
+    // Spans of `[:name:]`, which are ASCII only
+    let spans: &[(char, char)] = match name {
+        "alnum" => &[('0', '9'), ('A', 'Z'), ('a', 'z')],
+        "alpha" => &[('A', 'Z'), ('a', 'z')],
+        "ascii" => &[('\0', '\x7F')],
+        "blank" => &[('\t', '\t'), (' ', ' ')],
+        "cntrl" => &[('\0', '\x1F'), ('\x7F', '\x7F')],
+        "digit" => &[('0', '9')],
+        "graph" => &[('!', '~')],
+        "lower" => &[('a', 'z')],
+        "print" => &[(' ', '~')],
+        "punct" => &[('!', '/'), (':', '@'), ('[', '`'), ('{', '~')],
+        "space" => &[('\t', '\r'), (' ', ' ')],
+        "upper" => &[('A', 'Z')],
+        "word" => &[('0', '9'), ('A', 'Z'), ('_', '_'), ('a', 'z')],
+        "xdigit" => &[('0', '9'), ('A', 'F'), ('a', 'f')],
+        _ => return None,
+    };
+
+    Some(spans)
+}
+
+fn normalize(name: &str) -> String {
+    // Loose matching of names, so `Uppercase Letter` is `uppercaseletter`
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn canonical(property: &str, name: &str) -> Option<&'static str> {
+    // Full name of a value, from any of its aliases
+    let (_, values) = PROPERTY_VALUES.iter().find(|x| x.0 == property)?;
+    values.iter().find(|x| x.0 == name).map(|x| x.1)
+}
+
+fn table(
+    by_name: &'static [(&str, &'static [(char, char)])],
+    name: &str,
+) -> Option<&'static [(char, char)]> {
+    by_name.iter().find(|x| x.0 == name).map(|x| x.1)
+}
+
+pub fn property_class(name: &str) -> Option<&'static [(char, char)]> {
+    // Spans of a general category or script, as in `\p{Lu}` or `\p{Greek}`
+    let name = normalize(name);
+
+    if let Some(x) = canonical("General_Category", &name) {
+        return table(general_category::BY_NAME, x);
+    }
+
+    let x = canonical("Script", &name)?;
+    table(script::BY_NAME, x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_chars() {
+        for c in ['a', 'Z', '0', '_', 'é', 'ß', 'δ', '٣', '\u{301}'] {
+            assert!(is_word_char(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn non_word_chars() {
+        for c in [' ', '-', '\n', '€', '。', '\u{10FFFF}'] {
+            assert!(!is_word_char(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn perl_classes() {
+        assert!(in_table(perl_class('d', true).unwrap(), '٣'));
+        assert!(!in_table(perl_class('d', false).unwrap(), '٣'));
+        assert!(in_table(perl_class('s', true).unwrap(), '\u{3000}'));
+        assert!(in_table(perl_class('s', false).unwrap(), '\x0b'));
+        assert!(in_table(perl_class('w', false).unwrap(), '_'));
+        assert_eq!(perl_class('q', true), None);
+    }
+
+    #[test]
+    fn property_classes() {
+        let lu = property_class("Lu").unwrap();
+        assert!(in_table(lu, 'A'));
+        assert!(in_table(lu, 'Δ'));
+        assert!(!in_table(lu, 'a'));
+
+        assert_eq!(property_class("Uppercase_Letter"), Some(lu));
+        assert_eq!(property_class("uppercase letter"), Some(lu));
+
+        let greek = property_class("Greek").unwrap();
+        assert!(in_table(greek, 'λ'));
+        assert!(!in_table(greek, 'a'));
+        assert_eq!(property_class("grek"), Some(greek));
+
+        assert!(in_table(property_class("Han").unwrap(), '漢'));
+        assert!(in_table(property_class("L").unwrap(), 'ß'));
+        assert!(in_table(property_class("Nd").unwrap(), '٣'));
+        assert_eq!(property_class("Klingon"), None);
+    }
+
+    #[test]
+    fn case_variants_chars() {
+        let k: Vec<_> = case_variants('k', 'k').collect();
+        assert_eq!(k, vec!['K', '\u{212A}']);
+
+        let s: Vec<_> = case_variants('S', 'S').collect();
+        assert_eq!(s, vec!['s', '\u{17F}']);
+
+        assert_eq!(case_variants('1', '1').count(), 0);
+    }
+
+    #[test]
+    fn case_variants_span() {
+        let mut upper: Vec<_> = case_variants('a', 'z').collect();
+        upper.sort();
+
+        // 26 letters, plus KELVIN SIGN and LATIN SMALL LETTER LONG S
+        assert_eq!(upper.len(), 28);
+        assert_eq!(upper[0], 'A');
+        assert!(upper.contains(&'\u{212A}'));
+        assert!(upper.contains(&'\u{17F}'));
+    }
+
+    #[test]
+    fn posix_classes() {
+        let punct = posix_class("punct").unwrap();
+        let graph = posix_class("graph").unwrap();
+
+        for c in '\0'..='\x7F' {
+            assert_eq!(in_table(punct, c), c.is_ascii_punctuation(), "{c:?}");
+            assert_eq!(in_table(graph, c), c.is_ascii_graphic(), "{c:?}");
+        }
+
+        assert!(in_table(posix_class("space").unwrap(), '\x0b'));
+        assert!(!in_table(posix_class("blank").unwrap(), '\n'));
+        assert_eq!(posix_class("Alpha"), None);
+    }
+
+    #[test]
+    fn ascii_word_chars() {
+        assert!(is_ascii_word_char('a'));
+        assert!(is_ascii_word_char('_'));
+        assert!(!is_ascii_word_char('é'));
+    }
+}
@@ -0,0 +1,13 @@
+// Tables generated by `ucd-generate`, copied from `regex-syntax`.
+// See LICENSE-UNICODE for the terms of the Unicode data.
+#![allow(clippy::redundant_static_lifetimes)]
+// Not every table in a file is used
+#![allow(dead_code)]
+
+pub mod case_folding_simple;
+pub mod general_category;
+pub mod perl_decimal;
+pub mod perl_space;
+pub mod perl_word;
+pub mod property_values;
+pub mod script;
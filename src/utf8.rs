@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use crate::error::ErrorKind;
+use crate::graph::{Graph, Step};
+use crate::look::Look;
+use crate::value::Value;
+
+// Largest char encoded in 1, 2 and 3 bytes
+const MAX_LEN: [u32; 3] = [0x7F, 0x7FF, 0xFFFF];
+
+pub fn sequences(start: char, end: char) -> Vec<Vec<(u8, u8)>> {
+    // Splits chars `start..=end` into byte ranges, each sequence matching
+    // every combination of its ranges and together the UTF-8 of every char.
+    // Ranges are split where the encoded length changes, then until
+    // each continuation byte covers all or only part of its own range.
+    let mut out = Vec::new();
+    let mut stack = vec![(start as u32, end as u32)];
+
+    'ranges: while let Some((start, mut end)) = stack.pop() {
+        // Surrogates aren't chars
+        if start < 0xD800 && end > 0xDFFF {
+            stack.push((0xE000, end));
+            end = 0xD7FF;
+        }
+
+        for max in MAX_LEN {
+            if start <= max && max < end {
+                stack.push((max + 1, end));
+                stack.push((start, max));
+                continue 'ranges;
+            }
+        }
+
+        for bits in [6, 12, 18] {
+            let low = (1 << bits) - 1;
+
+            if start & !low != end & !low {
+                if start & low != 0 {
+                    stack.push(((start | low) + 1, end));
+                    stack.push((start, start | low));
+                    continue 'ranges;
+                }
+
+                if end & low != low {
+                    stack.push((end & !low, end));
+                    stack.push((start, (end & !low) - 1));
+                    continue 'ranges;
+                }
+            }
+        }
+
+        let mut a = [0; 4];
+        let mut b = [0; 4];
+        let a = char::from_u32(start)
+            .unwrap()
+            .encode_utf8(&mut a)
+            .as_bytes();
+        let b = char::from_u32(end).unwrap().encode_utf8(&mut b).as_bytes();
+
+        out.push(a.iter().zip(b).map(|(x, y)| (*x, *y)).collect());
+    }
+
+    out
+}
+
+fn byte_sequences(value: &Value) -> Vec<Vec<(u8, u8)>> {
+    // Byte ranges of the chars `value` matches, see `byte_graph`
+    let (spans, raw) = match value {
+        Value::Char(x) => (vec![(*x, *x)], false),
+        Value::Class(x) => (x.positive(), x.is_raw()),
+    };
+
+    let mut out = Vec::new();
+
+    for (start, end) in spans {
+        let start = if raw && start <= '\u{FF}' {
+            out.push(vec![(start as u8, end.min('\u{FF}') as u8)]);
+            '\u{100}'
+        } else {
+            start
+        };
+
+        if start <= end {
+            out.extend(sequences(start, end));
+        }
+    }
+
+    out
+}
+
+fn charge(left: &mut usize, limit: usize) -> Result<(), ErrorKind> {
+    *left = left
+        .checked_sub(1)
+        .ok_or(ErrorKind::SizeLimitExceeded(limit))?;
+    Ok(())
+}
+
+pub fn byte_graph(
+    graph: &Graph<Step<Value>>,
+    limit: usize,
+) -> Result<Graph<Step<Value>>, ErrorKind> {
+    // Same graph consuming the UTF-8 of each char a byte at a time, bytes being chars
+    // up to `\xFF`. Counter states are written out, as a char may take several bytes,
+    // so nodes and edges are charged against `limit`.
+    // Raw classes take chars up to `\xFF` as single bytes, so `(?-u:\xFF)` and `(?-u:.)`
+    // match any byte.
+    let mut out = Graph::default();
+    let mut left = limit;
+
+    for _ in 0..graph.len() {
+        charge(&mut left, limit)?;
+        out.new_node();
+    }
+
+    for node in 0..graph.len() {
+        for (step, next) in graph.edges(node) {
+            let Some(value) = &step.value else {
+                charge(&mut left, limit)?;
+                out.add_edge(node, next, step.clone());
+                continue;
+            };
+
+            // Sequences with the same first bytes share nodes
+            let mut shared = HashMap::new();
+
+            for seq in byte_sequences(value) {
+                let mut from = node;
+
+                for (i, &(lo, hi)) in seq.iter().enumerate() {
+                    // Only the first byte is after the saves and assertions
+                    let (saves, look) = match i {
+                        0 => (step.saves.clone(), step.look),
+                        _ => (Vec::new(), Look::default()),
+                    };
+
+                    let value = match lo == hi {
+                        true => Value::Char(lo as char),
+                        false => Value::class(&[(lo as char, hi as char)], false),
+                    };
+
+                    let to = if i + 1 == seq.len() {
+                        next
+                    } else if let Some(&x) = shared.get(&(from, lo, hi)) {
+                        from = x;
+                        continue;
+                    } else {
+                        charge(&mut left, limit)?;
+                        let x = out.new_node();
+                        shared.insert((from, lo, hi), x);
+                        x
+                    };
+
+                    charge(&mut left, limit)?;
+                    out.add_edge(
+                        from,
+                        to,
+                        Step {
+                            value: Some(value),
+                            saves,
+                            look,
+                        },
+                    );
+                    from = to;
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Class;
+
+    fn matches(seqs: &[Vec<(u8, u8)>], bytes: &[u8]) -> bool {
+        seqs.iter().any(|seq| {
+            seq.len() == bytes.len() && seq.iter().zip(bytes).all(|(r, b)| r.0 <= *b && *b <= r.1)
+        })
+    }
+
+    fn check(start: char, end: char) {
+        // Every char in the range matches, and the chars either side don't
+        let seqs = sequences(start, end);
+        let mut buf = [0; 4];
+
+        let outside = [start as u32 - 1, end as u32 + 1]
+            .into_iter()
+            .filter_map(char::from_u32);
+        let inside = (start..=end).step_by(((end as u32 - start as u32) / 5000).max(1) as usize);
+
+        for c in inside {
+            assert!(matches(&seqs, c.encode_utf8(&mut buf).as_bytes()), "{c:?}");
+        }
+
+        for c in outside.filter(|&x| x > '\0') {
+            assert!(!matches(&seqs, c.encode_utf8(&mut buf).as_bytes()), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(sequences('a', 'z'), vec![vec![(b'a', b'z')]]);
+    }
+
+    #[test]
+    fn two_bytes() {
+        assert_eq!(sequences('é', 'é'), vec![vec![(0xC3, 0xC3), (0xA9, 0xA9)]]);
+        check('\u{80}', '\u{7FF}');
+        check('\u{90}', '\u{123}');
+    }
+
+    #[test]
+    fn across_lengths() {
+        check('\u{1}', '\u{10FFFF}');
+        check('a', '\u{10000}');
+        check('\u{7F0}', '\u{801}');
+    }
+
+    #[test]
+    fn skips_surrogates() {
+        let seqs = sequences('\u{D000}', '\u{E100}');
+
+        check('\u{D000}', '\u{D7FF}');
+        assert!(!matches(&seqs, &[0xED, 0xA0, 0x80]));
+        assert!(matches(&seqs, "\u{E000}".as_bytes()));
+    }
+
+    #[test]
+    fn raw_bytes() {
+        let class = Class::new(&[('\0', '\u{FF}')], false);
+        let seqs = byte_sequences(&Value::Class(class.into_raw()));
+        assert_eq!(seqs, vec![vec![(0, 0xFF)]]);
+
+        let seqs = byte_sequences(&Value::Char('é'));
+        assert_eq!(seqs, vec![vec![(0xC3, 0xC3), (0xA9, 0xA9)]]);
+    }
+}
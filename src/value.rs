@@ -1,9 +1,14 @@
 use std::fmt::Display;
 
+use crate::unicode::case_variants;
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Class {
     spans: Vec<(char, char)>,
     inverse: bool,
+
+    // Chars up to `\xFF` are single bytes when matching bytes, see `byte_graph`
+    raw: bool,
 }
 
 impl Class {
@@ -32,8 +37,135 @@ impl Class {
         Class {
             spans: out,
             inverse,
+            raw: false,
+        }
+    }
+
+    pub fn into_raw(self) -> Self {
+        Class { raw: true, ..self }
+    }
+
+    pub fn is_raw(&self) -> bool {
+        self.raw
+    }
+
+    pub fn positive(&self) -> Vec<(char, char)> {
+        // Spans of every matched char, with `inverse` applied
+        if self.inverse {
+            complement(&self.spans)
+        } else {
+            self.spans.clone()
+        }
+    }
+
+    pub fn complement(&self) -> Self {
+        // Every char not matched, without `inverse`
+        Class {
+            spans: complement(&self.positive()),
+            inverse: false,
+            raw: false,
         }
     }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Class::new(&[self.positive(), other.positive()].concat(), false)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        // Walks both sorted span lists together
+        let (a, b) = (self.positive(), other.positive());
+        let (mut i, mut j) = (0, 0);
+        let mut out = Vec::new();
+
+        while i < a.len() && j < b.len() {
+            let start = a[i].0.max(b[j].0);
+            let end = a[i].1.min(b[j].1);
+
+            if start <= end {
+                out.push((start, end));
+            }
+
+            if a[i].1 < b[j].1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        Class::new(&out, false)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.difference(other).union(&other.difference(self))
+    }
+
+    pub fn case_fold(&self, unicode: bool) -> Self {
+        // Closes the spans under simple case folding, keeping `inverse`.
+        // Outside Unicode mode only ASCII letters fold.
+        let mut spans = self.spans.clone();
+
+        for &(start, end) in &self.spans {
+            spans.extend(fold_variants(start, end, unicode).map(|x| (x, x)));
+        }
+
+        Class::new(&spans, self.inverse)
+    }
+}
+
+fn fold_variants(start: char, end: char, unicode: bool) -> impl Iterator<Item = char> {
+    // Case variants of `start..=end`, ASCII only outside Unicode mode,
+    // where `k` leaves out the Kelvin sign
+    let end = if unicode { end } else { end.min('\x7F') };
+    case_variants(start, end).filter(move |x| unicode || x.is_ascii())
+}
+
+fn complement(spans: &[(char, char)]) -> Vec<(char, char)> {
+    // Gaps between sorted disjoint spans
+    let mut out = Vec::new();
+    let mut next = Some('\0');
+
+    for &(start, end) in spans {
+        if let Some(x) = next
+            && x < start
+        {
+            out.push((x, prev_char(start).unwrap()));
+        }
+
+        next = next_char(end);
+    }
+
+    if let Some(x) = next {
+        out.push((x, char::MAX));
+    }
+
+    out
+}
+
+pub fn boundaries(spans: &[(char, char)]) -> impl Iterator<Item = char> + '_ {
+    // The start of each span and the char after it
+    spans
+        .iter()
+        .flat_map(|&(start, end)| [Some(start), next_char(end)])
+        .flatten()
+}
+
+fn next_char(c: char) -> Option<char> {
+    // Skips surrogates, which aren't chars
+    match c {
+        '\u{D7FF}' => Some('\u{E000}'),
+        _ => char::from_u32(c as u32 + 1),
+    }
+}
+
+fn prev_char(c: char) -> Option<char> {
+    match c {
+        '\u{E000}' => Some('\u{D7FF}'),
+        _ => char::from_u32((c as u32).checked_sub(1)?),
+    }
 }
 
 impl Display for Class {
@@ -90,6 +222,31 @@ impl Value {
     pub fn class(spans: &[(char, char)], inverse: bool) -> Self {
         Self::Class(Class::new(spans, inverse))
     }
+
+    pub fn boundaries(&self) -> Vec<char> {
+        // Chars where `matches` can change between a char and the one before it
+        match self {
+            Value::Char(x) => boundaries(&[(*x, *x)]).collect(),
+            Value::Class(x) => boundaries(&x.spans).collect(),
+        }
+    }
+
+    pub fn case_fold(&self, unicode: bool) -> Self {
+        // Matches the same chars ignoring case, see `Class::case_fold`.
+        match self {
+            Value::Char(x) => {
+                let spans: Vec<_> = fold_variants(*x, *x, unicode).map(|y| (y, y)).collect();
+
+                if spans.is_empty() {
+                    Value::Char(*x)
+                } else {
+                    Value::class(&[spans, vec![(*x, *x)]].concat(), false)
+                }
+            }
+
+            Value::Class(x) => Value::Class(x.case_fold(unicode)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -198,4 +355,143 @@ mod tests {
         assert!(!v.matches('k'));
         assert!(!v.matches('l'));
     }
+
+    #[test]
+    fn positive() {
+        let c = Class::new(&[('b', 'd'), ('x', 'x')], false);
+        assert_eq!(c.positive(), vec![('b', 'd'), ('x', 'x')]);
+
+        let c = Class::new(&[('b', 'd'), ('x', 'x')], true);
+        let expected = vec![('\0', 'a'), ('e', 'w'), ('y', char::MAX)];
+        assert_eq!(c.positive(), expected);
+    }
+
+    #[test]
+    fn positive_edges() {
+        let c = Class::new(&[('\0', 'a'), ('z', char::MAX)], true);
+        assert_eq!(c.positive(), vec![('b', 'y')]);
+
+        let c = Class::new(&[], true);
+        assert_eq!(c.positive(), vec![('\0', char::MAX)]);
+
+        let c = Class::new(&[('\0', char::MAX)], true);
+        assert!(c.positive().is_empty());
+    }
+
+    #[test]
+    fn positive_surrogates() {
+        let c = Class::new(&[('\u{D7FF}', '\u{D7FF}'), ('\u{E000}', char::MAX)], true);
+        assert_eq!(c.positive(), vec![('\0', '\u{D7FE}')]);
+    }
+
+    #[test]
+    fn complement() {
+        let c = Class::new(&[('b', 'd')], false).complement();
+        assert_eq!(c, Class::new(&[('\0', 'a'), ('e', char::MAX)], false));
+
+        let c = Class::new(&[('b', 'd')], true).complement();
+        assert_eq!(c, Class::new(&[('b', 'd')], false));
+    }
+
+    #[test]
+    fn union() {
+        let a = Class::new(&[('a', 'c'), ('x', 'z')], false);
+        let b = Class::new(&[('d', 'f')], false);
+
+        assert_eq!(a.union(&b), Class::new(&[('a', 'f'), ('x', 'z')], false));
+    }
+
+    #[test]
+    fn intersection() {
+        let a = Class::new(&[('a', 'm'), ('p', 'z')], false);
+        let b = Class::new(&[('c', 'e'), ('k', 'r'), ('y', 'y')], false);
+        let expected = Class::new(&[('c', 'e'), ('k', 'm'), ('p', 'r'), ('y', 'y')], false);
+
+        assert_eq!(a.intersection(&b), expected);
+        assert_eq!(b.intersection(&a), expected);
+    }
+
+    #[test]
+    fn intersection_inverse() {
+        let a = Class::new(&[('a', 'z')], false);
+        let b = Class::new(&[('d', 'w')], true);
+
+        assert_eq!(
+            a.intersection(&b),
+            Class::new(&[('a', 'c'), ('x', 'z')], false)
+        );
+    }
+
+    #[test]
+    fn difference() {
+        let a = Class::new(&[('a', 'z')], false);
+        let b = Class::new(&[('a', 'a'), ('e', 'e'), ('z', 'z')], false);
+        let expected = Class::new(&[('b', 'd'), ('f', 'y')], false);
+
+        assert_eq!(a.difference(&b), expected);
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        let a = Class::new(&[('a', 'm')], false);
+        let b = Class::new(&[('h', 'z')], false);
+        let expected = Class::new(&[('a', 'g'), ('n', 'z')], false);
+
+        assert_eq!(a.symmetric_difference(&b), expected);
+    }
+
+    #[test]
+    fn case_fold_char() {
+        let v = Value::Char('a').case_fold(true);
+        assert_eq!(v, Value::class(&[('A', 'A'), ('a', 'a')], false));
+
+        let v = Value::Char('1').case_fold(true);
+        assert_eq!(v, Value::Char('1'));
+    }
+
+    #[test]
+    fn case_fold_special() {
+        let v = Value::Char('k').case_fold(true);
+        let expected = Value::class(&[('K', 'K'), ('k', 'k'), ('\u{212A}', '\u{212A}')], false);
+        assert_eq!(v, expected);
+
+        let v = Value::class(&[('a', 'z')], false).case_fold(true);
+        assert!(v.matches('\u{212A}'));
+        assert!(v.matches('\u{17F}'));
+    }
+
+    #[test]
+    fn case_fold_ascii() {
+        let v = Value::Char('k').case_fold(false);
+        assert_eq!(v, Value::class(&[('K', 'K'), ('k', 'k')], false));
+
+        let v = Value::Char('\u{E9}').case_fold(false);
+        assert_eq!(v, Value::Char('\u{E9}'));
+
+        let v = Value::Char('\u{212A}').case_fold(false);
+        assert_eq!(v, Value::Char('\u{212A}'));
+
+        let v = Value::class(&[('a', 'z')], false).case_fold(false);
+        assert!(v.matches('S'));
+        assert!(!v.matches('\u{17F}'));
+    }
+
+    #[test]
+    fn case_fold_large_class() {
+        // Every char but `a` already contains `A`
+        let v = Value::class(&[('a', 'a')], true).case_fold(true);
+        assert_eq!(v, Value::class(&[('A', 'A'), ('a', 'a')], true));
+
+        let c = Class::new(&[('a', 'a')], true);
+        let v = Value::class(&c.positive(), false).case_fold(true);
+        assert!(v.matches('a'));
+    }
+
+    #[test]
+    fn case_fold_class() {
+        let v = Value::class(&[('a', 'c'), ('X', 'X')], true).case_fold(true);
+        let expected = Value::class(&[('A', 'C'), ('X', 'X'), ('a', 'c'), ('x', 'x')], true);
+
+        assert_eq!(v, expected);
+    }
 }
@@ -0,0 +1,89 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn anchor_start() {
+    let r = Regex::new("^ab").unwrap();
+    assert!(r.has_match("abc"));
+    assert!(!r.has_match("cab"));
+    assert_eq!(r.search("abab").map(|m| m.range()), Some(0..2));
+}
+
+#[test]
+fn anchor_end() {
+    let r = Regex::new("ab$").unwrap();
+    assert!(r.has_match("cab"));
+    assert!(!r.has_match("abc"));
+    assert!(!r.has_match("ab\n"));
+    assert_eq!(r.search("abab").map(|m| m.range()), Some(2..4));
+}
+
+#[test]
+fn anchor_both() {
+    let r = Regex::new("^a*$").unwrap();
+    assert!(r.has_match(""));
+    assert!(r.has_match("aaa"));
+    assert!(!r.has_match("aab"));
+}
+
+#[test]
+fn anchor_check() {
+    let r = Regex::new("^abc$").unwrap();
+    assert!(r.check("abc"));
+    assert!(!r.check("abcd"));
+}
+
+#[test]
+fn anchor_text() {
+    let r = Regex::new("(?m)\\Aa|b\\z").unwrap();
+    assert_eq!(find_all("(?m)\\Aa|b\\z", "ab\nab"), vec!["a", "b"]);
+    assert_eq!(r.search("\nab").map(|m| m.range()), Some(2..3));
+}
+
+#[test]
+fn anchor_multi_line() {
+    assert_eq!(find_all("(?m)^[a-z]+$", "ab\ncd e\nfg"), vec!["ab", "fg"]);
+    assert!(find_all("^[a-z]+$", "ab\ncd").is_empty());
+}
+
+#[test]
+fn anchor_multi_line_empty() {
+    let r = Regex::new("(?m)^").unwrap();
+    let starts: Vec<_> = r.find_iter("a\n\nb\n").map(|m| m.start()).collect();
+    assert_eq!(starts, vec![0, 2, 3, 5]);
+}
+
+#[test]
+fn anchor_find_iter_start() {
+    // Only the first match touches the start of the text
+    assert_eq!(find_all("^a", "aaa"), vec!["a"]);
+}
+
+#[test]
+fn anchor_optional() {
+    // A failing assertion doesn't hide the path around it
+    let r = Regex::new("(?:^|x)b").unwrap();
+    assert_eq!(r.search("ab").map(|m| m.range()), None);
+    assert_eq!(r.search("xb").map(|m| m.range()), Some(0..2));
+    assert_eq!(r.search("b").map(|m| m.range()), Some(0..1));
+
+    let r = Regex::new("(?:^)?b").unwrap();
+    assert_eq!(r.search("ab").map(|m| m.range()), Some(1..2));
+}
+
+#[test]
+fn anchor_captures() {
+    let r = Regex::new("(?m)^([a-z]+)$").unwrap();
+    let caps = r.captures("12\nab\n").unwrap();
+    assert_eq!(caps.get(1).map(|m| m.as_str()), Some("ab"));
+}
+
+#[test]
+fn anchor_escaped() {
+    let r = Regex::new("\\^\\$").unwrap();
+    assert!(r.check("^$"));
+}
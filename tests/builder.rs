@@ -0,0 +1,92 @@
+use regex_engine::{ErrorKind, RegexBuilder};
+
+#[test]
+fn multi_line() {
+    let r = RegexBuilder::new("^b$").multi_line(true).build().unwrap();
+    assert!(r.has_match("a\nb\nc"));
+
+    let r = RegexBuilder::new("^b$").build().unwrap();
+    assert!(!r.has_match("a\nb\nc"));
+}
+
+#[test]
+fn dot_matches_new_line() {
+    let r = RegexBuilder::new("a.b")
+        .dot_matches_new_line(true)
+        .build()
+        .unwrap();
+    assert!(r.check("a\nb"));
+
+    let r = RegexBuilder::new("a.b").build().unwrap();
+    assert!(!r.check("a\nb"));
+}
+
+#[test]
+fn ignore_whitespace() {
+    let r = RegexBuilder::new("a b # comment\n c")
+        .ignore_whitespace(true)
+        .build()
+        .unwrap();
+    assert!(r.check("abc"));
+}
+
+#[test]
+fn ignore_whitespace_scoped_off() {
+    let r = RegexBuilder::new("a (?-x: b) c")
+        .ignore_whitespace(true)
+        .build()
+        .unwrap();
+    assert!(r.check("a bc"));
+}
+
+#[test]
+fn unicode_off() {
+    let r = RegexBuilder::new("\\w+").unicode(false).build().unwrap();
+    assert!(!r.check("café"));
+    assert!(r.check("cafe"));
+}
+
+#[test]
+fn nest_limit() {
+    let e = RegexBuilder::new("a((b)|[[c]])")
+        .nest_limit(1)
+        .build()
+        .unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::NestLimitExceeded(1));
+    assert_eq!(e.span(), 2..3);
+
+    assert!(
+        RegexBuilder::new("a((b)|[[c]])")
+            .nest_limit(3)
+            .build()
+            .is_ok()
+    );
+}
+
+#[test]
+fn nest_limit_default() {
+    let deep = "(".repeat(1000) + "a" + &")".repeat(1000);
+    let e = RegexBuilder::new(&deep).build().unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::NestLimitExceeded(_)));
+}
+
+#[test]
+fn size_limit() {
+    let e = RegexBuilder::new("a{100}")
+        .size_limit(50)
+        .build()
+        .unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::SizeLimitExceeded(50));
+    assert_eq!(e.span(), 0..6);
+
+    assert!(RegexBuilder::new("a{100}").size_limit(500).build().is_ok());
+}
+
+#[test]
+fn size_limit_default() {
+    // Would be billions of nodes if built
+    let e = RegexBuilder::new("((a{1000}){1000}){1000}")
+        .build()
+        .unwrap_err();
+    assert!(matches!(e.kind(), ErrorKind::SizeLimitExceeded(_)));
+}
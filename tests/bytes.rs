@@ -0,0 +1,207 @@
+use regex_engine::bytes::Regex;
+use regex_engine::{ErrorKind, RegexBuilder};
+
+#[test]
+fn same_as_str() {
+    // Valid UTF-8 gives the same matches as the str regex
+    let texts = ["", "abc 123", "δέκα 日本", "a\nb", "foo bar_baz", "ﬀ Σσ"];
+
+    for pattern in [
+        r"\w+",
+        r"\d+|[α-ω]+",
+        r"\bb",
+        "(?m)^b",
+        ".",
+        "[^a-z ]+",
+        "(?i)σ+",
+        "x*",
+        r"\B",
+        r"\b|$",
+        r"\w*",
+    ] {
+        let re = Regex::new(pattern).unwrap();
+        let other = regex_engine::Regex::new(pattern).unwrap();
+
+        for text in texts {
+            let found = re.search(text.as_bytes()).map(|x| x.range());
+            let all: Vec<_> = re.find_iter(text.as_bytes()).map(|x| x.range()).collect();
+            let expected: Vec<_> = other.find_iter(text).map(|x| x.range()).collect();
+
+            assert_eq!(
+                found,
+                other.search(text).map(|x| x.range()),
+                "{pattern} on {text:?}"
+            );
+            assert_eq!(
+                re.check(text.as_bytes()),
+                other.check(text),
+                "{pattern} on {text:?}"
+            );
+            assert_eq!(all, expected, "{pattern} on {text:?}");
+        }
+    }
+}
+
+#[test]
+fn empty_matches_between_chars() {
+    let all = |pattern, text: &str| {
+        let re = Regex::new(pattern).unwrap();
+        let all: Vec<_> = re.find_iter(text.as_bytes()).map(|x| x.range()).collect();
+        all
+    };
+
+    assert_eq!(all(r"\B", "é"), []);
+    assert_eq!(all("a*", "é"), [0..0, 2..2]);
+    assert_eq!(all("x?", "日"), [0..0, 3..3]);
+
+    // Invalid bytes are chars of their own
+    let re = Regex::new("x?").unwrap();
+    assert_eq!(re.find_iter(b"\xC3b").count(), 3);
+}
+
+#[test]
+fn invalid_utf8() {
+    let re = Regex::new(r"\w+").unwrap();
+    let text = b"ab\xFFcd\xC3";
+
+    let all: Vec<_> = re.find_iter(text).map(|x| x.as_bytes()).collect();
+    assert_eq!(all, [&b"ab"[..], b"cd"]);
+}
+
+#[test]
+fn byte_offsets() {
+    let re = Regex::new("é+").unwrap();
+    let m = re.search("aéé".as_bytes()).unwrap();
+
+    assert_eq!(m.range(), 1..5);
+    assert_eq!(m.as_bytes(), "éé".as_bytes());
+}
+
+#[test]
+fn dot_skips_invalid_bytes() {
+    let re = Regex::new("a.b").unwrap();
+
+    assert!(re.has_match("aδb".as_bytes()));
+    assert!(!re.has_match(b"a\xFFb"));
+}
+
+#[test]
+fn raw_bytes() {
+    let re = RegexBuilder::new(r"\xFF.")
+        .unicode(false)
+        .build_bytes()
+        .unwrap();
+
+    assert!(re.check(b"\xFF\x80"));
+    assert!(!re.check("ÿa".as_bytes()));
+    assert!(!re.check(b"\xFF\n"));
+
+    let re = RegexBuilder::new("(?s).+")
+        .unicode(false)
+        .build_bytes()
+        .unwrap();
+    assert!(re.check(b"\x00\xC3\n\xFF"));
+
+    let re = RegexBuilder::new(r"[^\x00-\x7F]+")
+        .unicode(false)
+        .build_bytes()
+        .unwrap();
+    assert_eq!(re.search(b"ab\x80\x81c").unwrap().range(), 2..4);
+}
+
+#[test]
+fn raw_bytes_inline() {
+    let re = Regex::new(r"(?-u)\xFF").unwrap();
+    assert!(re.check(b"\xFF"));
+    assert!(!re.check("ÿ".as_bytes()));
+
+    let re = Regex::new("(?-u:.)").unwrap();
+    assert!(re.check(b"\xFF"));
+
+    // Only ASCII letters fold, whatever the byte means in Latin-1
+    let re = Regex::new(r"(?i-u)\xE9").unwrap();
+    assert!(re.check(b"\xE9"));
+    assert!(!re.check(b"\xC9"));
+
+    // Scoped to the group
+    let re = Regex::new(r"(?-u:\xE9)\xE9").unwrap();
+    assert!(re.check(b"\xE9\xC3\xA9"));
+    assert!(!re.check(b"\xE9\xE9"));
+}
+
+#[test]
+fn raw_inside_chars() {
+    // Outside Unicode mode every byte is a place to start or assert at
+    let all = |pattern, text: &[u8]| {
+        let re = Regex::new(pattern).unwrap();
+        let all: Vec<_> = re.find_iter(text).map(|x| (x.start(), x.end())).collect();
+        all
+    };
+
+    assert_eq!(all(r"(?-u)\xA9", b"\xC3\xA9"), [(1, 2)]);
+    assert_eq!(all(r"(?s-u).", b"\xC3\xA9"), [(0, 1), (1, 2)]);
+    assert_eq!(all(r"(?-u)[^a]", b"\xC3\xA9"), [(0, 1), (1, 2)]);
+    assert_eq!(all(r"(?-u)x*", b"\xC3\xA9"), [(0, 0), (1, 1), (2, 2)]);
+}
+
+#[test]
+fn literal_chars_encode() {
+    // Chars written as themselves are their UTF-8, whatever the mode
+    let res = [
+        Regex::new("é").unwrap(),
+        Regex::new("(?-u)é").unwrap(),
+        RegexBuilder::new("é").unicode(false).build_bytes().unwrap(),
+    ];
+
+    for re in res {
+        assert!(re.check("é".as_bytes()), "{}", re.as_str());
+        assert!(!re.check(b"\xE9"), "{}", re.as_str());
+    }
+}
+
+#[test]
+fn unicode_mode_encodes() {
+    // `\xFF` is the char ÿ, so its two UTF-8 bytes
+    let re = Regex::new(r"\xFF").unwrap();
+
+    assert!(re.check("ÿ".as_bytes()));
+    assert!(!re.check(b"\xFF"));
+}
+
+#[test]
+fn word_boundaries() {
+    let re = Regex::new(r"\bδ\b").unwrap();
+
+    assert!(re.has_match("a δ b".as_bytes()));
+    assert!(!re.has_match("aδ".as_bytes()));
+    assert!(re.has_match(b"\xFF\xCE\xB4"));
+}
+
+#[test]
+fn captures() {
+    let re = Regex::new(r"(?<key>\w+)=(\w+)").unwrap();
+    let caps = re.captures(b"\xFF k=v\xFF").unwrap();
+
+    assert_eq!(caps.name("key").unwrap().as_bytes(), b"k");
+    assert_eq!(caps.get(2).unwrap().range(), 4..5);
+    assert_eq!(caps.iter().count(), 3);
+}
+
+#[test]
+fn counted() {
+    let re = Regex::new(r"\p{Greek}{3}").unwrap();
+
+    assert!(re.check("αβγ".as_bytes()));
+    assert!(!re.check("αβ".as_bytes()));
+}
+
+#[test]
+fn counted_too_large() {
+    // Counts are written out in bytes, a copy of the class's UTF-8 each
+    let e = Regex::new(r"\w{20000}").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::SizeLimitExceeded(100_000));
+    assert_eq!(e.span(), 0..9);
+
+    let re = Regex::new(r"\w{20}").unwrap();
+    assert!(re.check("é".repeat(20).as_bytes()));
+}
@@ -0,0 +1,117 @@
+use regex_engine::Regex;
+
+#[test]
+fn captures_whole_match() {
+    let r = Regex::new("[0-9]+").unwrap();
+    let caps = r.captures("abc 123 def").unwrap();
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(4..7));
+}
+
+#[test]
+fn captures_groups() {
+    let r = Regex::new("([0-9]{4})-([0-9]{2})-([0-9]{2})").unwrap();
+    let caps = r.captures("Date: 2024-01-31.").unwrap();
+
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(6..16));
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(6..10));
+    assert_eq!(caps.get(2).map(|m| m.range()), Some(11..13));
+    assert_eq!(caps.get(3).map(|m| m.range()), Some(14..16));
+    assert_eq!(caps.get(4).map(|m| m.range()), None);
+}
+
+#[test]
+fn captures_nested() {
+    let r = Regex::new("((a)(b))c").unwrap();
+    let caps = r.captures("xabc").unwrap();
+
+    let spans: Vec<_> = caps.iter().map(|m| m.map(|m| m.range())).collect();
+    let expected = vec![Some(1..4), Some(1..3), Some(1..2), Some(2..3)];
+    assert_eq!(spans, expected);
+}
+
+#[test]
+fn captures_unmatched_group() {
+    let r = Regex::new("(a)|(b)").unwrap();
+    let caps = r.captures("b").unwrap();
+
+    assert_eq!(caps.get(1).map(|m| m.range()), None);
+    assert_eq!(caps.get(2).map(|m| m.range()), Some(0..1));
+}
+
+#[test]
+fn captures_last_iteration() {
+    let r = Regex::new("([a-z])+").unwrap();
+    let caps = r.captures("abc").unwrap();
+
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(0..3));
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(2..3));
+}
+
+#[test]
+fn captures_greedy() {
+    let r = Regex::new("(a*)(a*)").unwrap();
+    let caps = r.captures("aaa").unwrap();
+
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(0..3));
+    assert_eq!(caps.get(2).map(|m| m.range()), Some(3..3));
+}
+
+#[test]
+fn captures_empty_match() {
+    let r = Regex::new("(b*)").unwrap();
+    let caps = r.captures("aaa").unwrap();
+
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(0..0));
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(0..0));
+}
+
+#[test]
+fn captures_same_as_search() {
+    let r = Regex::new("\\((a+)\\)|(a+)").unwrap();
+    let text = "f(a)aaaaaaaaaa";
+    let caps = r.captures(text).unwrap();
+
+    assert_eq!(
+        caps.get(0).map(|m| m.range()),
+        r.search(text).map(|m| m.range())
+    );
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(2..3));
+    assert_eq!(caps.get(2).map(|m| m.range()), None);
+}
+
+#[test]
+fn captures_fail() {
+    let r = Regex::new("([0-9]{3,})").unwrap();
+    assert!(r.captures("01, 23, 45, 67").is_none());
+}
+
+#[test]
+fn captures_named() {
+    let r = Regex::new("(?<year>[0-9]{4})-(?P<month>[0-9]{2})").unwrap();
+    let caps = r.captures("on 2024-01").unwrap();
+
+    assert_eq!(caps.name("year").map(|m| m.range()), Some(3..7));
+    assert_eq!(caps.name("month").map(|m| m.range()), Some(8..10));
+    assert_eq!(caps.name("day").map(|m| m.range()), None);
+    assert_eq!(
+        caps.get(2).map(|m| m.range()),
+        caps.name("month").map(|m| m.range())
+    );
+}
+
+#[test]
+fn captures_named_unmatched() {
+    let r = Regex::new("(?<a>a)|(?<b>b)").unwrap();
+    let caps = r.captures("b").unwrap();
+
+    assert_eq!(caps.name("a").map(|m| m.range()), None);
+    assert_eq!(caps.name("b").map(|m| m.range()), Some(0..1));
+}
+
+#[test]
+fn capture_names() {
+    let r = Regex::new("(?<first>a)(b)(?P<third>c)").unwrap();
+    let names: Vec<_> = r.capture_names().collect();
+
+    assert_eq!(names, vec![None, Some("first"), None, Some("third")]);
+}
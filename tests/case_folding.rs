@@ -0,0 +1,70 @@
+use regex_engine::{Regex, RegexBuilder};
+
+#[test]
+fn fold_kelvin_and_long_s() {
+    let r = Regex::new("(?i)[a-z]+").unwrap();
+    assert!(r.check("\u{212A}elvin"));
+    assert!(r.check("\u{17F}ign"));
+
+    let r = Regex::new("(?i)k").unwrap();
+    assert!(r.check("K"));
+    assert!(r.check("\u{212A}"));
+}
+
+#[test]
+fn fold_non_ascii() {
+    let r = Regex::new("(?i)straße δ").unwrap();
+    assert!(r.check("STRAẞE Δ"));
+
+    let r = Regex::new("(?i)σ").unwrap();
+    assert!(r.check("Σ"));
+    assert!(r.check("ς"));
+}
+
+#[test]
+fn fold_large_classes() {
+    let r = Regex::new("(?i)[^\\W\\d]+").unwrap();
+    assert_eq!(r.search("12 Abc").map(|m| m.as_str()), Some("Abc"));
+
+    let r = Regex::new("(?i)\\p{Ll}+").unwrap();
+    assert!(r.check("ABCdef"));
+}
+
+#[test]
+fn fold_negated_class() {
+    let r = Regex::new("(?i)[^k]").unwrap();
+    assert!(!r.check("K"));
+    assert!(!r.check("\u{212A}"));
+    assert!(r.check("j"));
+}
+
+#[test]
+fn fold_builder() {
+    let r = RegexBuilder::new("hello [a-c]+")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    assert!(r.check("HeLLo aBC"));
+
+    let r = RegexBuilder::new("a(?-i)b")
+        .case_insensitive(true)
+        .build()
+        .unwrap();
+    assert!(r.check("Ab"));
+    assert!(!r.check("AB"));
+
+    let r = RegexBuilder::new("a")
+        .case_insensitive(false)
+        .build()
+        .unwrap();
+    assert!(!r.check("A"));
+}
+
+#[test]
+fn fold_builder_error() {
+    let e = RegexBuilder::new("(a")
+        .case_insensitive(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(e.pattern(), "(a");
+}
@@ -0,0 +1,97 @@
+use regex_engine::{ErrorKind, Regex};
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn class_nested() {
+    assert_eq!(find_all("[a[0-9]]+", "xa1b2"), vec!["a1", "2"]);
+    assert_eq!(find_all("[[^a-z]x]+", "abX1xy"), vec!["X1x"]);
+}
+
+#[test]
+fn class_difference() {
+    assert_eq!(
+        find_all("[a-z--[aeiou]]+", "regex engine"),
+        vec!["r", "g", "x", "ng", "n"]
+    );
+    assert_eq!(find_all("[\\w--\\d]+", "ab12cd"), vec!["ab", "cd"]);
+}
+
+#[test]
+fn class_intersection() {
+    let r = Regex::new("[\\p{Greek}&&\\p{Lu}]+").unwrap();
+    assert_eq!(r.search("αβΓΔε").map(|m| m.as_str()), Some("ΓΔ"));
+    assert!(!r.check("A"));
+
+    assert_eq!(find_all("[a-m&&h-z]+", "abhijmnz"), vec!["hijm"]);
+}
+
+#[test]
+fn class_symmetric_difference() {
+    assert_eq!(find_all("[a-m~~h-z]+", "aghmnz"), vec!["ag", "nz"]);
+}
+
+#[test]
+fn class_left_associative() {
+    // ([a-z] -- [a-c]) && [a-e]
+    assert_eq!(find_all("[a-z--a-c&&a-e]+", "abcdefg"), vec!["de"]);
+}
+
+#[test]
+fn class_negated_with_operators() {
+    // Negation applies to the whole result
+    assert_eq!(find_all("[^a-z--aeiou]+", "bAe1c"), vec!["Ae1"]);
+
+    // Nested negation is a real complement
+    assert_eq!(find_all("[a-z&&[^aeiou]]+", "regex"), vec!["r", "g", "x"]);
+}
+
+#[test]
+fn class_operators_case_insensitive() {
+    assert_eq!(
+        find_all("(?i)[a-z--[aeiou]]+", "REgex"),
+        vec!["R", "g", "x"]
+    );
+}
+
+#[test]
+fn class_single_operator_chars() {
+    assert_eq!(find_all("[&~-]+", "a&~-b"), vec!["&~-"]);
+}
+
+#[test]
+fn class_operator_errors() {
+    let kind = |p: &str| Regex::new(p).err().map(|e| e.kind().clone());
+
+    assert_eq!(kind("[a&&]"), Some(ErrorKind::EmptyClass));
+    assert_eq!(kind("[--a]"), Some(ErrorKind::EmptyClass));
+    assert_eq!(kind("[a[b]"), Some(ErrorKind::IncompleteClass));
+    assert_eq!(kind("[a[]]"), Some(ErrorKind::EmptyClass));
+}
+
+#[test]
+fn class_nested_matches_nothing() {
+    // Written items that evaluate to nothing aren't an empty class
+    for pattern in ["[[a&&b]]", "[[a--a]&&b]"] {
+        let r = Regex::new(pattern).unwrap();
+        assert!(!r.has_match("ab"), "{pattern}");
+    }
+
+    assert_eq!(find_all("[[a&&b]c]+", "abcc"), vec!["cc"]);
+}
+
+#[test]
+fn class_operands_case_insensitive() {
+    // Each operand matches either case before the operator applies
+    assert_eq!(find_all("(?i)[a-z--[AEIOU]]+", "hello"), vec!["h", "ll"]);
+    assert!(!Regex::new("(?i)[a-z&&[^K]]").unwrap().has_match("k"));
+    assert!(!Regex::new("(?i)[S~~[:^digit:]]").unwrap().has_match("s"));
+    assert!(
+        Regex::new(r"(?i)[^[:alpha:]~~\W~~\p{Lu}]")
+            .unwrap()
+            .check("a")
+    );
+}
@@ -0,0 +1,96 @@
+use regex_engine::{ErrorKind, Regex};
+
+fn compare(pattern: &str, texts: &[&str]) {
+    let re = Regex::new(pattern).unwrap();
+    let dfa = re.to_dfa().unwrap();
+
+    for text in texts {
+        assert_eq!(dfa.check(text), re.check(text), "{pattern} on {text:?}");
+        assert_eq!(
+            dfa.has_match(text),
+            re.has_match(text),
+            "{pattern} on {text:?}"
+        );
+    }
+}
+
+#[test]
+fn same_as_regex() {
+    let texts = ["", "a", "ab", "abb", "aabb", "babb", "xyz", "abab", "ba"];
+    compare("(a|b)*abb", &texts);
+    compare("a+b?", &texts);
+    compare("[^a]", &texts);
+    compare("a{2,3}", &texts);
+    compare("x?y?z?", &texts);
+    compare("ab|ba", &texts);
+}
+
+#[test]
+fn assertions() {
+    let texts = ["foo", "a foo", "foobar", "éfoo", "foo\nbar", "bar\n"];
+    compare(r"\bfoo\b", &texts);
+    compare(r"(?m)^bar$", &texts);
+    compare(r"\Bfoo", &texts);
+    compare(r"^foo", &texts);
+    compare(r"foo\z", &texts);
+}
+
+#[test]
+fn unicode() {
+    let texts = ["δέκα", "日本", "ab", "Σ", "σ"];
+    compare(r"\w+", &texts);
+    compare(r"\p{Greek}", &texts);
+    compare("(?i)σ+", &texts);
+}
+
+#[test]
+fn minimal() {
+    // Different NFAs for the same language give the same DFA
+    let a = Regex::new("a*").unwrap().to_dfa().unwrap();
+    let b = Regex::new("(a|aa)*").unwrap().to_dfa().unwrap();
+    let c = Regex::new("(a*)*a*").unwrap().to_dfa().unwrap();
+
+    assert_eq!(a.len(), b.len());
+    assert_eq!(a.len(), c.len());
+}
+
+#[test]
+fn shortest_match() {
+    let dfa = Regex::new("a+").unwrap().to_dfa().unwrap();
+
+    assert_eq!(dfa.shortest_match("xaaa"), Some(2));
+    assert_eq!(dfa.shortest_match("xyz"), None);
+    assert_eq!(
+        Regex::new("a*")
+            .unwrap()
+            .to_dfa()
+            .unwrap()
+            .shortest_match("b"),
+        Some(0)
+    );
+}
+
+#[test]
+fn state_limit() {
+    let re = Regex::new("(a|b)*a(a|b){12}").unwrap();
+    let err = re.to_dfa_with_limit(1000).unwrap_err();
+
+    assert_eq!(err.kind(), &ErrorKind::StateLimitExceeded(1000));
+    assert_eq!(err.span(), 0..re.as_str().len());
+
+    let re = Regex::new("(a|b)*a(a|b){5}").unwrap();
+    assert!(re.to_dfa_with_limit(1000).is_ok());
+}
+
+#[test]
+fn state_limit_bounds_work() {
+    // Few states, but each has hundreds of NFA nodes to step on every class
+    let re = Regex::new("\\w{1,200}").unwrap();
+    let err = re.to_dfa_with_limit(1000).unwrap_err();
+
+    assert_eq!(err.kind(), &ErrorKind::StateLimitExceeded(1000));
+    assert!(re.to_dfa().is_err());
+
+    let re = Regex::new("\\w{1,5}").unwrap();
+    assert!(re.to_dfa_with_limit(1000).is_ok());
+}
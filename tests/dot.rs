@@ -0,0 +1,54 @@
+use regex_engine::Regex;
+
+#[test]
+fn dot_any_char() {
+    let r = Regex::new("a.c").unwrap();
+    assert!(r.check("abc"));
+    assert!(r.check("a.c"));
+    assert!(r.check("aéc"));
+    assert!(!r.check("ac"));
+}
+
+#[test]
+fn dot_excludes_newline() {
+    let r = Regex::new("a.c").unwrap();
+    assert!(!r.check("a\nc"));
+    assert!(r.check("a\rc"));
+
+    let r = Regex::new(".+").unwrap();
+    assert_eq!(r.search("ab\ncd").map(|m| m.as_str()), Some("ab"));
+}
+
+#[test]
+fn dot_all() {
+    let r = Regex::new("(?s)a.c").unwrap();
+    assert!(r.check("a\nc"));
+
+    let r = Regex::new("(?s:.+)").unwrap();
+    assert_eq!(r.search("ab\ncd").map(|m| m.as_str()), Some("ab\ncd"));
+}
+
+#[test]
+fn dot_crlf() {
+    let r = Regex::new("(?R).+").unwrap();
+    assert_eq!(r.search("ab\r\ncd").map(|m| m.as_str()), Some("ab"));
+
+    // `s` still matches everything
+    let r = Regex::new("(?Rs)a.c").unwrap();
+    assert!(r.check("a\rc"));
+}
+
+#[test]
+fn dot_case_insensitive() {
+    let r = Regex::new("(?i)a.").unwrap();
+    assert!(r.check("Ab"));
+    assert!(!r.check("A\n"));
+}
+
+#[test]
+fn dot_escaped() {
+    let r = Regex::new("a\\.c|[.]").unwrap();
+    assert!(r.check("a.c"));
+    assert!(r.check("."));
+    assert!(!r.check("abc"));
+}
@@ -0,0 +1,115 @@
+use regex_engine::{ErrorKind, Regex};
+
+fn kind(pattern: &str) -> Option<ErrorKind> {
+    Regex::new(pattern).err().map(|e| e.kind().clone())
+}
+
+#[test]
+fn error_unknown_escape() {
+    assert_eq!(kind("a\\q"), Some(ErrorKind::UnknownEscape('q')));
+}
+
+#[test]
+fn error_unclosed_group() {
+    assert_eq!(kind("(ab|c"), Some(ErrorKind::UnclosedGroup));
+}
+
+#[test]
+fn error_unmatched_paren() {
+    assert_eq!(kind("ab)c"), Some(ErrorKind::UnmatchedParen));
+}
+
+#[test]
+fn error_nothing_to_repeat() {
+    assert_eq!(kind("*a"), Some(ErrorKind::NothingToRepeat('*')));
+}
+
+#[test]
+fn error_incomplete_class() {
+    assert_eq!(kind("[a-z"), Some(ErrorKind::IncompleteClass));
+}
+
+#[test]
+fn error_unclosed_group_span() {
+    let e = Regex::new("ab(cd(ef)gh").unwrap_err();
+    assert_eq!(e.span(), 2..3);
+}
+
+#[test]
+fn error_quantifier_span() {
+    let e = Regex::new("ab|*").unwrap_err();
+    assert_eq!(e.span(), 3..4);
+}
+
+#[test]
+fn error_class_range_span() {
+    let e = Regex::new("a[b-az]").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::InvalidClassRange('b', 'a'));
+    assert_eq!(e.span(), 2..5);
+}
+
+#[test]
+fn error_expected_found() {
+    let e = Regex::new("a{2x}").unwrap_err();
+    assert_eq!(e.kind().to_string(), "expected `}` or `,`, found `x`");
+}
+
+#[test]
+fn error_display() {
+    let e = Regex::new("abc(d*").unwrap_err();
+    let expected = "\
+error: unclosed group opened at 3
+  |
+1 | abc(d*
+  |    ^";
+
+    assert_eq!(e.to_string(), expected);
+}
+
+#[test]
+fn error_display_multibyte() {
+    let e = Regex::new("éé\\q").unwrap_err();
+    let expected = "\
+error: unknown escape `\\q` at 4
+  |
+1 | éé\\q
+  |   ^^";
+
+    assert_eq!(e.to_string(), expected);
+}
+
+#[test]
+fn error_duplicate_group_name() {
+    let e = Regex::new("(?<x>a)|(?<x>b)").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::DuplicateGroupName("x".to_string()));
+    assert_eq!(e.span(), 8..13);
+}
+
+#[test]
+fn error_invalid_group_name() {
+    let e = Regex::new("(?P<a-b>x)").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::InvalidGroupName("a-b".to_string()));
+    assert_eq!(
+        e.to_string().lines().next(),
+        Some("error: invalid group name `a-b` at 4")
+    );
+}
+
+#[test]
+fn error_unknown_property() {
+    let e = Regex::new("a[\\p{Klingon}]").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::UnknownProperty("Klingon".to_string()));
+    assert_eq!(e.span(), 2..13);
+    assert_eq!(kind("\\p{Lu"), Some(ErrorKind::UnclosedProperty));
+}
+
+#[test]
+fn error_posix_class() {
+    let e = Regex::new("[a[:alfa:]]").unwrap_err();
+    assert_eq!(e.kind(), &ErrorKind::UnknownPosixClass("alfa".to_string()));
+    assert_eq!(e.span(), 2..10);
+    assert_eq!(
+        kind("[[:alpha]"),
+        Some(ErrorKind::UnclosedPosixClass(Some(']')))
+    );
+}
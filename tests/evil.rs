@@ -2,49 +2,64 @@ use regex_engine::Regex;
 
 #[test]
 fn evil_union() {
-    let r = Regex::new("(a|a|a|b|a|a|a|a|a)+");
+    let r = Regex::new("(a|a|a|b|a|a|a|a|a)+").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(res);
 }
 
 #[test]
 fn evil_union_fail() {
-    let r = Regex::new("(a|a)+");
+    let r = Regex::new("(a|a)+").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(!res);
 }
 
 #[test]
 fn evil_multiple_quantifier() {
-    let r = Regex::new("((a+)*b)*");
+    let r = Regex::new("((a+)*b)*").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(res);
 }
 
 #[test]
 fn evil_multiple_quantifier_fail() {
-    let r = Regex::new("((a+)*)*");
+    let r = Regex::new("((a+)*)*").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(!res);
 }
 
 #[test]
 fn evil_repeated_pattern() {
-    let r = Regex::new("a*a*b?a*a*");
+    let r = Regex::new("a*a*b?a*a*").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(res);
 }
 
 #[test]
 fn evil_repeated_pattern_fail() {
-    let r = Regex::new("a*a*");
+    let r = Regex::new("a*a*").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaab");
     assert!(!res);
 }
 
 #[test]
 fn evil_fixed_quantifier_fail() {
-    let r = Regex::new("(a|b)*b(b|a){16}");
+    let r = Regex::new("(a|b)*b(b|a){16}").unwrap();
     let res = r.check("aaaaaaaaaaaaaaaabaaaaaaaaabaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaac");
     assert!(!res);
 }
+
+#[test]
+fn evil_long_literal() {
+    // Nests as deep as it is long, more than the stack allows if recursed
+    let text = "a".repeat(40000);
+    let r = Regex::new(&text).unwrap();
+    assert!(r.check(&text));
+    assert!(!r.check(&text[1..]));
+}
+
+#[test]
+fn evil_long_empty_run() {
+    let r = Regex::new(&"a{0}".repeat(20000)).unwrap();
+    assert!(r.check(""));
+}
@@ -0,0 +1,77 @@
+use std::ops::Range;
+
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<Range<usize>> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.range()).collect()
+}
+
+#[test]
+fn find_iter_words() {
+    let res = find_all("[A-Za-z]+", "> Good morning, world!");
+    assert_eq!(res, vec![2..6, 7..14, 16..21]);
+}
+
+#[test]
+fn find_iter_adjacent() {
+    let res = find_all("ab", "ababxab");
+    assert_eq!(res, vec![0..2, 2..4, 5..7]);
+}
+
+#[test]
+fn find_iter_first() {
+    let res = find_all("a|ab|abc", "abcab");
+    assert_eq!(res, vec![0..1, 3..4]);
+}
+
+#[test]
+fn find_iter_none() {
+    let res = find_all("[0-9]{3,}", "01, 23, 45, 67");
+    assert_eq!(res, vec![]);
+}
+
+#[test]
+fn find_iter_empty_text() {
+    let res = find_all("a*", "");
+    assert_eq!(res, vec![0..0]);
+}
+
+#[test]
+fn find_iter_empty_matches() {
+    let res = find_all("x*", "abc");
+    assert_eq!(res, vec![0..0, 1..1, 2..2, 3..3]);
+}
+
+#[test]
+fn find_iter_empty_after_match() {
+    let res = find_all("a*", "baab");
+    assert_eq!(res, vec![0..0, 1..3, 4..4]);
+}
+
+#[test]
+fn find_iter_unicode() {
+    let res = find_all("é+", "aéébé");
+    assert_eq!(res, vec![1..5, 6..8]);
+}
+
+#[test]
+fn find_iter_matches_search() {
+    let r = Regex::new("\\((a+)\\)|a+").unwrap();
+    let text = "f(a)aaaaaaaaaa";
+
+    assert_eq!(r.find_iter(text).next(), r.search(text));
+}
+
+#[test]
+fn find_iter_match_text() {
+    let r = Regex::new("[a-zé]+").unwrap();
+    let words: Vec<_> = r.find_iter("Café au lait").map(|m| m.as_str()).collect();
+    assert_eq!(words, vec!["afé", "au", "lait"]);
+}
+
+#[test]
+fn find_iter_empty_unicode() {
+    let res = find_all("x*", "éa");
+    assert_eq!(res, vec![0..0, 2..2, 3..3]);
+}
@@ -0,0 +1,123 @@
+use regex_engine::Regex;
+
+#[test]
+fn flags_non_capture() {
+    let r = Regex::new("(?:ab)+(c)").unwrap();
+    let caps = r.captures("ababc").unwrap();
+
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(0..5));
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(4..5));
+    assert_eq!(caps.get(2).map(|m| m.range()), None);
+}
+
+#[test]
+fn flags_case_insensitive() {
+    let r = Regex::new("(?i)hello [a-c]+").unwrap();
+    assert!(r.check("HeLLo aBC"));
+    assert!(!r.check("HeLLo abd"));
+}
+
+#[test]
+fn flags_case_insensitive_scoped() {
+    let r = Regex::new("a(?i:b)c").unwrap();
+    assert!(r.check("aBc"));
+    assert!(!r.check("aBC"));
+    assert!(!r.check("ABc"));
+}
+
+#[test]
+fn flags_case_insensitive_group() {
+    let r = Regex::new("a((?i)b)c").unwrap();
+    assert!(r.check("aBc"));
+    assert!(!r.check("aBC"));
+}
+
+#[test]
+fn flags_case_insensitive_inverse_class() {
+    let r = Regex::new("(?i)[^a]").unwrap();
+    assert!(!r.check("a"));
+    assert!(!r.check("A"));
+    assert!(r.check("b"));
+}
+
+#[test]
+fn flags_case_insensitive_ascii() {
+    // Outside Unicode mode only ASCII letters fold
+    let r = Regex::new("(?i-u)s").unwrap();
+    assert!(r.check("S"));
+    assert!(!r.check("\u{17F}"));
+
+    assert!(!Regex::new("(?i-u)[SS]").unwrap().check("\u{17F}"));
+    assert!(!Regex::new("(?i-u)é").unwrap().check("É"));
+
+    let r = Regex::new("(?i-u)[k&&a-fk]").unwrap();
+    assert!(r.check("K"));
+    assert!(!r.check("\u{212A}"));
+}
+
+#[test]
+fn flags_disable() {
+    let r = Regex::new("(?i)a(?-i)b").unwrap();
+    assert!(r.check("Ab"));
+    assert!(!r.check("AB"));
+}
+
+#[test]
+fn flags_disable_scoped() {
+    let r = Regex::new("(?i)a(?-i:b)c").unwrap();
+    assert!(r.check("AbC"));
+    assert!(!r.check("ABC"));
+}
+
+#[test]
+fn flags_union() {
+    let r = Regex::new("a(?i)b|c").unwrap();
+    assert!(r.check("aB"));
+    assert!(r.check("C"));
+}
+
+#[test]
+fn flags_ignore_whitespace() {
+    let pattern = "(?x)
+        ([0-9]{4}) - # year
+        ([0-9]{2})   # month
+        \\ [a-z]+    # escaped space
+    ";
+
+    let r = Regex::new(pattern).unwrap();
+    let caps = r.captures("2024-01 jan").unwrap();
+
+    assert_eq!(caps.get(0).map(|m| m.range()), Some(0..11));
+    assert_eq!(caps.get(2).map(|m| m.range()), Some(5..7));
+}
+
+#[test]
+fn flags_ignore_whitespace_quantifier() {
+    let r = Regex::new("(?x) a + b").unwrap();
+    assert!(r.check("aaab"));
+}
+
+#[test]
+fn flags_ignore_whitespace_class() {
+    let r = Regex::new("(?x)[ a # comment ]\n b]+").unwrap();
+    assert!(r.check("abba"));
+    assert!(!r.check("a b"));
+    assert!(!r.check("#"));
+
+    let r = Regex::new("(?x)[\\ a]+").unwrap();
+    assert!(r.check(" a a"));
+}
+
+#[test]
+fn flags_ignore_whitespace_scoped() {
+    let r = Regex::new("(?x: a b ) c").unwrap();
+    assert!(r.check("ab c"));
+    assert!(!r.check("abc"));
+}
+
+#[test]
+fn flags_scoped_after_named_group() {
+    let r = Regex::new("((?<n>a)(?x) ) b").unwrap();
+    assert!(r.check("a b"));
+    assert!(!r.check("ab"));
+}
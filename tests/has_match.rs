@@ -2,56 +2,56 @@ use regex_engine::Regex;
 
 #[test]
 fn has_match_exact_literal() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.has_match("the");
     assert!(res);
 }
 
 #[test]
 fn has_match_partial_literal() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.has_match("another");
     assert!(res);
 }
 
 #[test]
 fn has_match_words() {
-    let r = Regex::new("(they're|their|there)");
+    let r = Regex::new("(they're|their|there)").unwrap();
     let res = r.has_match("They are over there");
     assert!(res);
 }
 
 #[test]
 fn has_match_quantifier() {
-    let r = Regex::new("ab*");
+    let r = Regex::new("ab*").unwrap();
     let res = r.has_match("aaaaaaaa");
     assert!(res);
 }
 
 #[test]
 fn has_match_number() {
-    let r = Regex::new("[0-9]+");
+    let r = Regex::new("[0-9]+").unwrap();
     let res = r.has_match("There are 7 days in a week");
     assert!(res);
 }
 
 #[test]
 fn has_match_empty() {
-    let r = Regex::new("b*");
+    let r = Regex::new("b*").unwrap();
     let res = r.has_match("aaaaaaaaaaaaaaaaa");
     assert!(res);
 }
 
 #[test]
 fn has_match_evil_fail() {
-    let r = Regex::new("(a|a)*b");
+    let r = Regex::new("(a|a)*b").unwrap();
     let res = r.has_match("aaaaaaaaaaaaaaaaa");
     assert!(!res);
 }
 
 #[test]
 fn has_match_quantifier_fail() {
-    let r = Regex::new("b+");
+    let r = Regex::new("b+").unwrap();
     let res = r.has_match("aaaaaaaaaaaaaaaaa");
     assert!(!res);
 }
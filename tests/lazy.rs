@@ -0,0 +1,85 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn lazy_plus() {
+    let text = "<a href='x'>link</a> <b>bold</b>";
+    assert_eq!(
+        find_all("<.+?>", text),
+        ["<a href='x'>", "</a>", "<b>", "</b>"]
+    );
+    assert_eq!(find_all("<.+>", text), [text]);
+}
+
+#[test]
+fn lazy_star() {
+    assert_eq!(find_all("a.*?b", "axxbyyb azb"), ["axxb", "azb"]);
+    assert_eq!(find_all("a*?", "aa"), ["", "", ""]);
+}
+
+#[test]
+fn lazy_question() {
+    assert_eq!(find_all("ab??", "abab"), ["a", "a"]);
+    assert_eq!(find_all("ab??c", "abcac"), ["abc", "ac"]);
+}
+
+#[test]
+fn lazy_range() {
+    assert_eq!(find_all("\\d{2,4}?", "123456789"), ["12", "34", "56", "78"]);
+    assert_eq!(find_all("(ab){1,3}?", "ababab"), ["ab", "ab", "ab"]);
+    assert_eq!(find_all("x{2,}?", "xxxxx"), ["xx", "xx"]);
+}
+
+#[test]
+fn lazy_still_matches_whole() {
+    // Lazy quantifiers take more when the rest requires it
+    let r = Regex::new("a+?b").unwrap();
+    assert_eq!(r.search("caaab").map(|m| m.range()), Some(1..5));
+    assert!(r.check("aaab"));
+}
+
+#[test]
+fn lazy_captures() {
+    let r = Regex::new("(a+?)(a*)").unwrap();
+    let caps = r.captures("aaa").unwrap();
+
+    assert_eq!(caps.get(1).map(|m| m.range()), Some(0..1));
+    assert_eq!(caps.get(2).map(|m| m.range()), Some(1..3));
+}
+
+#[test]
+fn alternation_priority() {
+    assert_eq!(find_all("foo|foobar", "foobar"), ["foo"]);
+    assert_eq!(find_all("foobar|foo", "foobar"), ["foobar"]);
+}
+
+#[test]
+fn invalid_double_lazy() {
+    assert!(Regex::new("a*??").is_err());
+    assert!(Regex::new("a{2}??").is_err());
+}
+
+#[test]
+fn nullable_star() {
+    // An iteration matching nothing ends the repetition with its own priority
+    for (pattern, text) in [
+        ("(?:a??)*", "a"),
+        ("(?:a?|b)*", "b"),
+        ("(?:a*?)*", "aa"),
+        ("(?:^|x)*", "xx"),
+        ("(?:\\b|a)*", "ab"),
+    ] {
+        let r = Regex::new(pattern).unwrap();
+
+        assert_eq!(r.search(text).unwrap().range(), 0..0, "{pattern}");
+        assert_eq!(r.captures(text).unwrap().get(0).unwrap().range(), 0..0);
+        assert_eq!(r.to_dfa().unwrap().shortest_match(text), Some(0));
+    }
+
+    assert_eq!(find_all("(?:a?b?)*", "abba"), ["abba"]);
+    assert_eq!(find_all("(?:a?)*?b", "aab"), ["aab"]);
+}
@@ -0,0 +1,78 @@
+use regex_engine::Regex;
+
+// Same results as the `regex` crate, which the lazy DFA must not change
+fn compare(pattern: &str, texts: &[&str]) {
+    let re = Regex::new(pattern).unwrap();
+    let other = regex::Regex::new(pattern).unwrap();
+    let full = regex::Regex::new(&format!(r"\A(?:{pattern})\z")).unwrap();
+
+    for text in texts {
+        let found = other.find(text).map(|x| (x.start(), x.end()));
+
+        assert_eq!(
+            re.search(text).map(|x| (x.start(), x.end())),
+            found,
+            "{pattern} on {text:?}"
+        );
+        assert_eq!(re.has_match(text), found.is_some(), "{pattern} on {text:?}");
+        assert_eq!(re.check(text), full.is_match(text), "{pattern} on {text:?}");
+    }
+}
+
+#[test]
+fn literals_and_classes() {
+    let texts = ["", "a", "abc", "xxabcxx", "ab", "aabbcc", "cba"];
+    compare("abc", &texts);
+    compare("[a-b]+", &texts);
+    compare("[^a]c", &texts);
+    compare("a|ab|abc", &texts);
+}
+
+#[test]
+fn repetition() {
+    let texts = ["", "aaaa", "baaab", "aaaaaaaaaab", "abab"];
+    compare("a*", &texts);
+    compare("a+b", &texts);
+    compare("a{2,3}", &texts);
+    compare("(ab)+", &texts);
+    compare("a+?", &texts);
+    compare("(a|b)*?b", &texts);
+}
+
+#[test]
+fn assertions() {
+    let texts = ["foo", "a foo b", "afoo foo", "éfoo", "foo\nbar", "\nfoo\n"];
+    compare(r"\bfoo\b", &texts);
+    compare(r"\Bfoo", &texts);
+    compare(r"(?m)^foo$", &texts);
+    compare(r"^foo", &texts);
+    compare(r"foo$", &texts);
+    compare(r"(?-u:\b)foo", &texts);
+}
+
+#[test]
+fn unicode() {
+    let texts = ["δέκα", "a δ b", "日本語", "ﬀ", ""];
+    compare(r"\w+", &texts);
+    compare(r"\p{Greek}+", &texts);
+    compare("(?i)Δ", &texts);
+    compare(".", &texts);
+}
+
+#[test]
+fn many_states() {
+    // More states than the cache keeps, so it is cleared or given up on
+    let text: String = (0..5000)
+        .map(|x| if x * 7 % 11 < 5 { 'a' } else { 'b' })
+        .collect();
+    compare("(a|b)*a(a|b){12}b", &[&text]);
+    compare("a(a|b){12}bbbbbbbbbbbb", &[&text]);
+}
+
+#[test]
+fn iterators() {
+    let re = Regex::new(r"\b\w+\b").unwrap();
+    let words: Vec<_> = re.find_iter("one two, three").map(|x| x.as_str()).collect();
+
+    assert_eq!(words, ["one", "two", "three"]);
+}
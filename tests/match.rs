@@ -2,35 +2,35 @@ use regex_engine::Regex;
 
 #[test]
 fn match_literal() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.check("the");
     assert!(res);
 }
 
 #[test]
 fn match_hex() {
-    let r = Regex::new("\\x61\\x62\\x63");
+    let r = Regex::new("\\x61\\x62\\x63").unwrap();
     let res = r.check("abc");
     assert!(res);
 }
 
 #[test]
 fn match_quantifiers() {
-    let r = Regex::new("a*b?c+");
+    let r = Regex::new("a*b?c+").unwrap();
     let res = r.check("aaaabc");
     assert!(res);
 }
 
 #[test]
 fn match_range_quantifiers() {
-    let r = Regex::new("(a{2,}b{2,4}c{1,})*");
+    let r = Regex::new("(a{2,}b{2,4}c{1,})*").unwrap();
     let res = r.check("aabbcaaaabbbbccc");
     assert!(res);
 }
 
 #[test]
 fn match_unbounded_quantifier() {
-    let r = Regex::new("a{3,}");
+    let r = Regex::new("a{3,}").unwrap();
     assert!(!r.check(""));
     assert!(!r.check("a"));
     assert!(!r.check("aa"));
@@ -41,7 +41,7 @@ fn match_unbounded_quantifier() {
 
 #[test]
 fn match_bounded_quantifier() {
-    let r = Regex::new("[0-9]{3,5}");
+    let r = Regex::new("[0-9]{3,5}").unwrap();
     assert!(!r.check(""));
     assert!(!r.check("1"));
     assert!(!r.check("12"));
@@ -56,42 +56,42 @@ fn match_bounded_quantifier() {
 
 #[test]
 fn match_union() {
-    let r = Regex::new("one|two|three");
+    let r = Regex::new("one|two|three").unwrap();
     let res = r.check("two");
     assert!(res);
 }
 
 #[test]
 fn match_brackets() {
-    let r = Regex::new("(b|c|d)at?");
+    let r = Regex::new("(b|c|d)at?").unwrap();
     let res = r.check("cat");
     assert!(res);
 }
 
 #[test]
 fn match_literal_fail() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.check("there");
     assert!(!res);
 }
 
 #[test]
 fn match_digits() {
-    let r = Regex::new("[0-9]*");
+    let r = Regex::new("[0-9]*").unwrap();
     let res = r.check("1203912");
     assert!(res);
 }
 
 #[test]
 fn match_non_digits() {
-    let r = Regex::new("[^0-9]*");
+    let r = Regex::new("[^0-9]*").unwrap();
     let res = r.check("one thousand");
     assert!(res);
 }
 
 #[test]
 fn match_digits_fail() {
-    let r = Regex::new("[0-9]*");
+    let r = Regex::new("[0-9]*").unwrap();
     let res = r.check("0913a");
     assert!(!res);
 }
@@ -0,0 +1,94 @@
+use regex_engine::{MatchKind, Regex, RegexBuilder};
+
+fn longest(pattern: &str) -> Regex {
+    RegexBuilder::new(pattern)
+        .match_kind(MatchKind::LeftmostLongest)
+        .build()
+        .unwrap()
+}
+
+fn find_all(r: &Regex, text: &str) -> Vec<String> {
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn first_is_default() {
+    let r = Regex::new("foo|foobar").unwrap();
+    assert_eq!(r.search("foobar").map(|m| m.as_str()), Some("foo"));
+}
+
+#[test]
+fn first_alternation() {
+    let r = RegexBuilder::new("foo|foobar")
+        .match_kind(MatchKind::LeftmostFirst)
+        .build()
+        .unwrap();
+    assert_eq!(r.search("foobar").map(|m| m.as_str()), Some("foo"));
+}
+
+#[test]
+fn longest_alternation() {
+    let r = longest("foo|foobar");
+    assert_eq!(r.search("foobar").map(|m| m.as_str()), Some("foobar"));
+}
+
+#[test]
+fn both_leftmost() {
+    // An earlier start wins over a longer match in both
+    let text = "xabcd";
+    assert_eq!(
+        Regex::new("bcd|xa").unwrap().search(text).unwrap().range(),
+        0..2
+    );
+    assert_eq!(longest("bcd|xa").search(text).unwrap().range(), 0..2);
+}
+
+#[test]
+fn first_find_iter() {
+    let r = Regex::new("a|ab|abc").unwrap();
+    assert_eq!(find_all(&r, "abcab"), ["a", "a"]);
+}
+
+#[test]
+fn longest_find_iter() {
+    let r = longest("a|ab|abc");
+    assert_eq!(find_all(&r, "abcab"), ["abc", "ab"]);
+}
+
+#[test]
+fn longest_ignores_lazy() {
+    let r = longest("<.+?>");
+    assert_eq!(find_all(&r, "<a><b>"), ["<a><b>"]);
+}
+
+#[test]
+fn first_captures() {
+    let r = Regex::new("(a|ab)(c|bcd)").unwrap();
+    let caps = r.captures("abcd").unwrap();
+
+    assert_eq!(caps.get(0).unwrap().as_str(), "abcd");
+    assert_eq!(caps.get(1).unwrap().as_str(), "a");
+    assert_eq!(caps.get(2).unwrap().as_str(), "bcd");
+}
+
+#[test]
+fn longest_captures() {
+    let r = longest("(a|ab)(c|bcde?)");
+    let caps = r.captures("abcde").unwrap();
+
+    assert_eq!(caps.get(0).unwrap().as_str(), "abcde");
+    assert_eq!(caps.get(1).unwrap().as_str(), "a");
+    assert_eq!(caps.get(2).unwrap().as_str(), "bcde");
+}
+
+#[test]
+fn longest_captures_same_as_search() {
+    let r = longest("x*|(x+y)");
+    let caps = r.captures("xxy").unwrap();
+
+    assert_eq!(
+        caps.get(0).map(|m| m.range()),
+        r.search("xxy").map(|m| m.range())
+    );
+    assert_eq!(caps.get(1).unwrap().as_str(), "xxy");
+}
@@ -0,0 +1,68 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn perl_digit() {
+    assert_eq!(find_all("\\d+", "a1 23 b456"), vec!["1", "23", "456"]);
+    assert_eq!(find_all("\\D+", "a1 23"), vec!["a", " "]);
+}
+
+#[test]
+fn perl_word() {
+    assert_eq!(find_all("\\w+", "foo_bar, baz!"), vec!["foo_bar", "baz"]);
+    assert_eq!(find_all("\\W+", "foo_bar, baz!"), vec![", ", "!"]);
+}
+
+#[test]
+fn perl_space() {
+    assert_eq!(find_all("\\s+", "a \t\nb"), vec![" \t\n"]);
+    assert_eq!(find_all("\\S+", "a \t\nbc"), vec!["a", "bc"]);
+}
+
+#[test]
+fn perl_unicode() {
+    assert_eq!(find_all("\\d+", "x٣٤y"), vec!["٣٤"]);
+    assert_eq!(find_all("\\w+", "naïve café"), vec!["naïve", "café"]);
+    assert!(Regex::new("\\s").unwrap().check("\u{3000}"));
+}
+
+#[test]
+fn perl_ascii() {
+    assert_eq!(find_all("(?-u)\\d+", "x٣٤5y"), vec!["5"]);
+    assert_eq!(find_all("(?-u:\\w+)", "naïve"), vec!["na", "ve"]);
+    assert!(!Regex::new("(?-u)\\s").unwrap().check("\u{3000}"));
+    assert!(Regex::new("(?-u)\\W").unwrap().check("é"));
+}
+
+#[test]
+fn perl_in_class() {
+    assert_eq!(find_all("[\\d_-]+", "a1_2-3b"), vec!["1_2-3"]);
+    assert_eq!(find_all("[\\s,]+", "a, b ,c"), vec![", ", " ,"]);
+}
+
+#[test]
+fn perl_negated_in_class() {
+    // Everything but digits, or a `5`
+    assert_eq!(find_all("[\\D5]+", "ab12c5d"), vec!["ab", "c5d"]);
+
+    // Neither word chars nor spaces
+    assert_eq!(find_all("[^\\w\\s]+", "a, b!?"), vec![",", "!?"]);
+
+    // Everything is either a word char or not
+    assert!(Regex::new("^[\\w\\W]+$").unwrap().check("a\n-é"));
+}
+
+#[test]
+fn perl_case_insensitive() {
+    assert_eq!(find_all("(?i)\\w+", "Hello World"), vec!["Hello", "World"]);
+    assert_eq!(find_all("(?i)[\\w.]+", "Ab C.d"), vec!["Ab", "C.d"]);
+}
+
+#[test]
+fn perl_class_range_error() {
+    assert!(Regex::new("[a-\\d]").is_err());
+}
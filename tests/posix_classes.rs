@@ -0,0 +1,59 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn posix_each_class() {
+    let cases = [
+        ("alnum", "a1Z", "_ "),
+        ("alpha", "aZ", "1_"),
+        ("blank", " \t", "\na"),
+        ("cntrl", "\0\n\x7F", " a"),
+        ("digit", "09", "a٣"),
+        ("graph", "a!~", " \n"),
+        ("lower", "az", "A1"),
+        ("print", " a~", "\n\x7F"),
+        ("punct", "!_~", "a "),
+        ("space", " \t\n\x0B", "a_"),
+        ("upper", "AZ", "a1"),
+        ("xdigit", "0aF", "gG"),
+    ];
+
+    for (name, yes, no) in cases {
+        let r = Regex::new(&format!("[[:{name}:]]")).unwrap();
+
+        for c in yes.chars() {
+            assert!(r.check(&c.to_string()), "{name} {c:?}");
+        }
+
+        for c in no.chars() {
+            assert!(!r.check(&c.to_string()), "{name} {c:?}");
+        }
+    }
+}
+
+#[test]
+fn posix_with_other_items() {
+    assert_eq!(find_all("[[:digit:]_.]+", "a1_2.3b"), vec!["1_2.3"]);
+    assert_eq!(find_all("[[:upper:][:digit:]]+", "aB1c"), vec!["B1"]);
+}
+
+#[test]
+fn posix_negated() {
+    assert_eq!(find_all("[^[:alnum:]]+", "ab, cd!"), vec![", ", "!"]);
+    assert_eq!(find_all("[[:^alpha:]]+", "ab12cd"), vec!["12"]);
+}
+
+#[test]
+fn posix_ascii_only() {
+    assert!(!Regex::new("[[:alpha:]]").unwrap().check("é"));
+}
+
+#[test]
+fn posix_not_outside_brackets() {
+    // Without the outer brackets it is a class of `:`, `a`, `l`, `p` and `h`
+    assert_eq!(find_all("[:alpha:]+", "ab: hx"), vec!["a", ":", "h"]);
+}
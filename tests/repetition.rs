@@ -0,0 +1,119 @@
+use regex_engine::{ErrorKind, Regex, RegexBuilder};
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn counted_bounded() {
+    let r = Regex::new("a{2,4}").unwrap();
+    assert!(!r.check("a"));
+    assert!(r.check("aa"));
+    assert!(r.check("aaaa"));
+    assert!(!r.check("aaaaa"));
+}
+
+#[test]
+fn counted_exact() {
+    let r = Regex::new("x[0-9]{3}y").unwrap();
+    assert!(r.check("x123y"));
+    assert!(!r.check("x12y"));
+    assert!(!r.check("x1234y"));
+}
+
+#[test]
+fn counted_unbounded() {
+    let r = Regex::new("a{3,}b").unwrap();
+    assert!(!r.check("aab"));
+    assert!(r.check("aaab"));
+    assert!(r.check("aaaaaaaab"));
+}
+
+#[test]
+fn counted_optional() {
+    let r = Regex::new("ab{0,2}c").unwrap();
+    assert!(r.check("ac"));
+    assert!(r.check("abbc"));
+    assert!(!r.check("abbbc"));
+}
+
+#[test]
+fn counted_in_loop() {
+    let r = Regex::new("(a{2}b)+").unwrap();
+    assert!(r.check("aabaab"));
+    assert!(!r.check("aabab"));
+}
+
+#[test]
+fn counted_search() {
+    assert_eq!(
+        find_all("\\d{2,3}", "1 12 1234 12345"),
+        ["12", "123", "123", "45"]
+    );
+    assert_eq!(find_all("\\b\\w{3}\\b", "ab abc abcd xyz"), ["abc", "xyz"]);
+}
+
+#[test]
+fn counted_captures() {
+    let r = Regex::new("(x{2,3})(x*)").unwrap();
+    let caps = r.captures("xxxxx").unwrap();
+    assert_eq!(caps.get(1).unwrap().as_str(), "xxx");
+    assert_eq!(caps.get(2).unwrap().as_str(), "xx");
+}
+
+#[test]
+fn counted_case_insensitive() {
+    let r = Regex::new("(?i)k{2}").unwrap();
+    assert!(r.check("kK"));
+    assert!(r.check("\u{212A}k"));
+}
+
+#[test]
+fn counted_stays_small() {
+    // One node per count, rather than a copy of the class for each
+    let r = RegexBuilder::new("\\d{1,500}").size_limit(510).build();
+    assert!(r.is_ok());
+
+    let r = Regex::new("[\\u0000-\\uffff]{65535}").unwrap();
+    assert!(r.check(&"a".repeat(65535)));
+    assert!(!r.check(&"a".repeat(65534)));
+
+    let r = Regex::new("\\w{50000}").unwrap();
+    assert!(r.check(&"é".repeat(50000)));
+    assert!(!r.check(&"é".repeat(49999)));
+}
+
+#[test]
+fn estimate_too_large() {
+    for pattern in [
+        "(a{1000}){1000}",
+        "((ab){65535}){65535}",
+        "((a|b){200}){200}",
+    ] {
+        let e = Regex::new(pattern).unwrap_err();
+        assert_eq!(e.kind(), &ErrorKind::SizeLimitExceeded(100_000));
+        assert_eq!(e.span(), 0..pattern.len());
+    }
+}
+
+#[test]
+fn compaction_too_large() {
+    // Each optional copy is skipped into every later one, so the NFA grows
+    // with the square of the count, found without building all of it
+    for pattern in ["(?:a?){2000}", "(?:a?){5000}"] {
+        let e = Regex::new(pattern).unwrap_err();
+        assert_eq!(e.kind(), &ErrorKind::SizeLimitExceeded(100_000));
+    }
+
+    assert!(Regex::new("(?:a?){100}").unwrap().check("aaa"));
+}
+
+#[test]
+fn large_union() {
+    let words: Vec<_> = (0..5000).map(|x| format!("w{x}x")).collect();
+    let r = Regex::new(&words.join("|")).unwrap();
+
+    assert!(r.check("w4999x"));
+    assert!(!r.check("w5000x"));
+}
@@ -0,0 +1,123 @@
+use std::borrow::Cow;
+
+use regex_engine::{Captures, NoExpand, Regex};
+
+#[test]
+fn replace_first() {
+    let r = Regex::new("[0-9]+").unwrap();
+    assert_eq!(r.replace("a1 b22 c333", "#"), "a# b22 c333");
+}
+
+#[test]
+fn replace_all() {
+    let r = Regex::new("[0-9]+").unwrap();
+    assert_eq!(r.replace_all("a1 b22 c333", "#"), "a# b# c#");
+}
+
+#[test]
+fn replacen() {
+    let r = Regex::new("[0-9]+").unwrap();
+    assert_eq!(r.replacen("a1 b22 c333", 2, "#"), "a# b# c333");
+    assert_eq!(r.replacen("a1 b22 c333", 0, "#"), "a# b# c#");
+}
+
+#[test]
+fn replace_borrows_without_match() {
+    let r = Regex::new("[0-9]+").unwrap();
+    assert!(matches!(r.replace_all("abc", "#"), Cow::Borrowed("abc")));
+    assert!(matches!(r.replace_all("a1", "#"), Cow::Owned(_)));
+}
+
+#[test]
+fn replace_numbered_groups() {
+    let r = Regex::new("([0-9]{4})-([0-9]{2})-([0-9]{2})").unwrap();
+    assert_eq!(
+        r.replace_all("on 2024-01-31.", "$3/$2/$1"),
+        "on 31/01/2024."
+    );
+}
+
+#[test]
+fn replace_named_groups() {
+    let r = Regex::new("(?<last>\\w+), (?<first>\\w+)").unwrap();
+    assert_eq!(r.replace("Doe, Jane", "$first $last"), "Jane Doe");
+    assert_eq!(r.replace("Doe, Jane", "${first}_$last"), "Jane_Doe");
+}
+
+#[test]
+fn replace_braces_end_name() {
+    let r = Regex::new("(a)").unwrap();
+
+    // `$1b` refers to a group named `1b`, which doesn't exist
+    assert_eq!(r.replace("a", "$1b"), "");
+    assert_eq!(r.replace("a", "${1}b"), "ab");
+}
+
+#[test]
+fn replace_dollar_escapes() {
+    let r = Regex::new("[0-9]+").unwrap();
+    assert_eq!(r.replace("cost 5", "$$$0"), "cost $5");
+    assert_eq!(r.replace("cost 5", "$ $"), "cost $ $");
+    assert_eq!(r.replace("cost 5", "${0"), "cost ${0");
+    assert_eq!(r.replace("cost 5", NoExpand("$0")), "cost $0");
+}
+
+#[test]
+fn replace_missing_groups() {
+    let r = Regex::new("(a)|(b)").unwrap();
+    assert_eq!(r.replace_all("ab", "[$1$2$3]"), "[a][b]");
+}
+
+#[test]
+fn replace_closure() {
+    let r = Regex::new("(?<n>[0-9]+)").unwrap();
+    let doubled = r.replace_all("1 2 30", |caps: &Captures| {
+        let n: u32 = caps.name("n").unwrap().as_str().parse().unwrap();
+        (n * 2).to_string()
+    });
+    assert_eq!(doubled, "2 4 60");
+}
+
+#[test]
+fn replace_closure_str() {
+    let r = Regex::new("\\w+").unwrap();
+    let mut count = 0;
+    let out = r.replace_all("a b c", |_: &Captures| {
+        count += 1;
+        if count == 2 { "B" } else { "_" }
+    });
+    assert_eq!(out, "_ B _");
+}
+
+#[test]
+fn replace_empty_matches() {
+    let r = Regex::new("x*").unwrap();
+    assert_eq!(r.replace_all("abc", "-"), "-a-b-c-");
+    assert_eq!(r.replace_all("axxb", "-"), "-a-b-");
+}
+
+#[test]
+fn replace_unicode() {
+    let r = Regex::new("é+").unwrap();
+    assert_eq!(r.replace_all("caféé ét", "e"), "cafe et");
+}
+
+#[test]
+fn captures_iter() {
+    let r = Regex::new("(\\w)(\\d)").unwrap();
+    let pairs: Vec<_> = r
+        .captures_iter("a1 b2 c")
+        .map(|caps| (caps.get(1).unwrap().as_str(), caps.get(2).unwrap().as_str()))
+        .collect();
+    assert_eq!(pairs, [("a", "1"), ("b", "2")]);
+}
+
+#[test]
+fn expand() {
+    let r = Regex::new("(?<y>\\d+)-(\\d+)").unwrap();
+    let caps = r.captures("10-20").unwrap();
+
+    let mut dst = String::from(">");
+    caps.expand("$2..${y}$$", &mut dst);
+    assert_eq!(dst, ">20..10$");
+}
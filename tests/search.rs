@@ -2,42 +2,42 @@ use regex_engine::Regex;
 
 #[test]
 fn search_exact_text() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.search("the");
-    assert_eq!(res, Some((0, 3)))
+    assert_eq!(res.map(|m| m.range()), Some(0..3))
 }
 
 #[test]
 fn search_text() {
-    let r = Regex::new("the");
+    let r = Regex::new("the").unwrap();
     let res = r.search("another");
-    assert_eq!(res, Some((3, 6)))
+    assert_eq!(res.map(|m| m.range()), Some(3..6))
 }
 
 #[test]
 fn search_fixed_size_word() {
-    let r = Regex::new("[A-Za-z]{5}");
+    let r = Regex::new("[A-Za-z]{5}").unwrap();
     let res = r.search("It was a sunny afternoon.");
-    assert_eq!(res, Some((9, 14)))
+    assert_eq!(res.map(|m| m.range()), Some(9..14))
 }
 
 #[test]
 fn search_word() {
-    let r = Regex::new("[A-Za-z]+");
+    let r = Regex::new("[A-Za-z]+").unwrap();
     let res = r.search("> Good morning!");
-    assert_eq!(res, Some((2, 6)))
+    assert_eq!(res.map(|m| m.range()), Some(2..6))
 }
 
 #[test]
 fn search_pattern() {
-    let r = Regex::new("\\(a+\\)|a+");
+    let r = Regex::new("\\(a+\\)|a+").unwrap();
     let res = r.search("f(a)aaaaaaaaaa");
-    assert_eq!(res, Some((1, 4)))
+    assert_eq!(res.map(|m| m.range()), Some(1..4))
 }
 
 #[test]
 fn search_fail() {
-    let r = Regex::new("[0-9]{3,}");
+    let r = Regex::new("[0-9]{3,}").unwrap();
     let res = r.search("01, 23, 45, 67");
     assert_eq!(res, None)
 }
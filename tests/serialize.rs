@@ -0,0 +1,173 @@
+use regex_engine::{DeserializeError, Dfa, MatchKind, Regex, RegexBuilder};
+
+fn aligned(bytes: &[u8], buf: &mut Vec<u8>) -> std::ops::Range<usize> {
+    // Copy of `bytes` at an offset of `buf` aligned to 4
+    buf.resize(bytes.len() + 4, 0);
+    let offset = buf.as_ptr().align_offset(4);
+
+    buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    offset..offset + bytes.len()
+}
+
+fn swap_words(bytes: &[u8]) -> Vec<u8> {
+    // Same data written with the other byte order, the magic isn't a word
+    let mut out = bytes[..4].to_vec();
+
+    for x in bytes[4..].chunks(4) {
+        out.extend(x.iter().rev());
+    }
+
+    out
+}
+
+#[test]
+fn regex_round_trip() {
+    let re = Regex::new(r"(?<year>\d{4})-(\d{2})|\bx*?y{2,5}(?m:$)|[^a-z\p{Greek}]").unwrap();
+    let loaded = Regex::from_bytes(&re.to_bytes()).unwrap();
+
+    assert_eq!(loaded.as_str(), re.as_str());
+    assert!(loaded.capture_names().eq(re.capture_names()));
+
+    for text in ["on 2024-05 or", "xyyy", "zyy\n", "δ", "abc", "ab1"] {
+        assert_eq!(loaded.check(text), re.check(text));
+        assert_eq!(loaded.search(text), re.search(text));
+        assert_eq!(loaded.captures(text), re.captures(text));
+    }
+}
+
+#[test]
+fn match_kind_kept() {
+    let re = RegexBuilder::new("a|ab")
+        .match_kind(MatchKind::LeftmostLongest)
+        .build()
+        .unwrap();
+    let loaded = Regex::from_bytes(&re.to_bytes()).unwrap();
+
+    assert_eq!(loaded.search("ab").unwrap().as_str(), "ab");
+}
+
+#[test]
+fn regex_other_byte_order() {
+    let re = Regex::new("[a-c]+x").unwrap();
+    let loaded = Regex::from_bytes(&swap_words(&re.to_bytes())).unwrap();
+
+    assert_eq!(loaded.search("zbcax").unwrap().range(), 1..5);
+}
+
+#[test]
+fn dfa_round_trip() {
+    let dfa = Regex::new(r"\b[a-z]+@[a-z]+\b").unwrap().to_dfa().unwrap();
+    let bytes = dfa.to_bytes();
+
+    let mut buf = Vec::new();
+    let range = aligned(&bytes, &mut buf);
+    let loaded = Dfa::from_bytes(&buf[range]).unwrap();
+
+    assert_eq!(loaded.len(), dfa.len());
+    assert!(loaded.has_match("mail me@home now"));
+    assert!(!loaded.has_match("mail me@ home"));
+    assert_eq!(loaded.clone().into_owned(), dfa);
+}
+
+#[test]
+fn dfa_needs_alignment() {
+    let bytes = Regex::new("a").unwrap().to_dfa().unwrap().to_bytes();
+
+    let mut buf = Vec::new();
+    let range = aligned(&[&[0][..], &bytes].concat(), &mut buf);
+    let unaligned = &buf[range.start + 1..range.end];
+
+    assert_eq!(Dfa::from_bytes(unaligned), Err(DeserializeError::Unaligned));
+}
+
+#[test]
+fn dfa_other_byte_order() {
+    let bytes = Regex::new("a").unwrap().to_dfa().unwrap().to_bytes();
+
+    let mut buf = Vec::new();
+    let range = aligned(&swap_words(&bytes), &mut buf);
+
+    assert_eq!(
+        Dfa::from_bytes(&buf[range]),
+        Err(DeserializeError::ForeignEndian)
+    );
+}
+
+#[test]
+fn header_errors() {
+    let bytes = Regex::new("a").unwrap().to_bytes();
+
+    assert_eq!(
+        Regex::from_bytes(&bytes[..10]).unwrap_err(),
+        DeserializeError::Truncated
+    );
+    assert_eq!(
+        Regex::from_bytes(b"nope, not a regex").unwrap_err(),
+        DeserializeError::BadMagic
+    );
+
+    let mut version = bytes.clone();
+    version[8..12].copy_from_slice(&7u32.to_ne_bytes());
+    assert_eq!(
+        Regex::from_bytes(&version).unwrap_err(),
+        DeserializeError::UnsupportedVersion(7)
+    );
+
+    let dfa = Regex::new("a").unwrap().to_dfa().unwrap().to_bytes();
+    assert_eq!(
+        Regex::from_bytes(&dfa).unwrap_err(),
+        DeserializeError::WrongKind
+    );
+}
+
+#[test]
+fn corrupt_data_errors() {
+    // Changing or dropping any word gives an error or a working regex, never a panic
+    let re = Regex::new(r"(a|b{2,3})+[^x-z]\b(?<n>c*?)$").unwrap();
+    let bytes = re.to_bytes();
+
+    for i in (16..bytes.len()).step_by(4) {
+        for x in [0, 1, 2, 3, 0xFF, 0xD800, u32::MAX] {
+            let mut corrupt = bytes.clone();
+            corrupt[i..i + 4].copy_from_slice(&x.to_ne_bytes());
+
+            if let Ok(loaded) = Regex::from_bytes(&corrupt) {
+                loaded.search("abbbq c");
+                loaded.captures("aq");
+                loaded.check("bb-");
+            }
+        }
+
+        assert!(Regex::from_bytes(&bytes[..i]).is_err());
+    }
+}
+
+#[test]
+fn corrupt_dfa_errors() {
+    let bytes = Regex::new(r"[a-z]+\d")
+        .unwrap()
+        .to_dfa()
+        .unwrap()
+        .to_bytes();
+    let mut buf = Vec::new();
+
+    for i in (16..bytes.len()).step_by(4) {
+        for x in [0, 1, 5, 0x7F, 0xD800, u32::MAX] {
+            let mut corrupt = bytes.clone();
+            corrupt[i..i + 4].copy_from_slice(&x.to_ne_bytes());
+
+            let range = aligned(&corrupt, &mut buf);
+
+            if let Ok(loaded) = Dfa::from_bytes(&buf[range]) {
+                loaded.check("abc1");
+                loaded.has_match("zz9é");
+            }
+        }
+    }
+
+    let range = aligned(&bytes[..bytes.len() - 4], &mut buf);
+    assert_eq!(
+        Dfa::from_bytes(&buf[range]),
+        Err(DeserializeError::Truncated)
+    );
+}
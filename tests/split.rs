@@ -0,0 +1,53 @@
+use regex_engine::Regex;
+
+fn split<'t>(pattern: &str, text: &'t str) -> Vec<&'t str> {
+    Regex::new(pattern).unwrap().split(text).collect()
+}
+
+fn splitn<'t>(pattern: &str, text: &'t str, limit: usize) -> Vec<&'t str> {
+    Regex::new(pattern).unwrap().splitn(text, limit).collect()
+}
+
+#[test]
+fn split_fields() {
+    assert_eq!(split(",\\s*", "a, b,c,  d"), ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn split_no_match() {
+    assert_eq!(split(",", "abc"), ["abc"]);
+    assert_eq!(split(",", ""), [""]);
+}
+
+#[test]
+fn split_empty_fields() {
+    assert_eq!(split(",", ",a,,b,"), ["", "a", "", "b", ""]);
+    assert_eq!(split(",", ","), ["", ""]);
+}
+
+#[test]
+fn split_empty_matches() {
+    assert_eq!(split("x?", "abc"), ["", "a", "b", "c", ""]);
+    assert_eq!(split("x*", "axxb"), ["", "a", "b", ""]);
+}
+
+#[test]
+fn split_unicode() {
+    assert_eq!(split("\\s+", "héllo  wörld ✓"), ["héllo", "wörld", "✓"]);
+    assert_eq!(split("x?", "é✓"), ["", "é", "✓", ""]);
+}
+
+#[test]
+fn splitn_limit() {
+    assert_eq!(splitn(",", "a,b,c,d", 2), ["a", "b,c,d"]);
+    assert_eq!(splitn(",", "a,b,c,d", 4), ["a", "b", "c", "d"]);
+    assert_eq!(splitn(",", "a,b,c,d", 10), ["a", "b", "c", "d"]);
+}
+
+#[test]
+fn splitn_edges() {
+    assert_eq!(splitn(",", "a,b", 0), Vec::<&str>::new());
+    assert_eq!(splitn(",", "a,b", 1), ["a,b"]);
+    assert_eq!(splitn(",", "a,b,", 3), ["a", "b", ""]);
+    assert_eq!(splitn(",", "", 2), [""]);
+}
@@ -0,0 +1,54 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn property_general_category() {
+    assert_eq!(find_all("\\p{Lu}+", "abcDEFghiΔΣ"), vec!["DEF", "ΔΣ"]);
+    assert_eq!(find_all("\\p{L}+", "ab1 cé2"), vec!["ab", "cé"]);
+    assert_eq!(find_all("\\p{Nd}+", "a12٣٤"), vec!["12٣٤"]);
+}
+
+#[test]
+fn property_long_names() {
+    let r = Regex::new("\\p{Uppercase_Letter}").unwrap();
+    assert!(r.check("A"));
+
+    let r = Regex::new("\\p{decimal number}").unwrap();
+    assert!(r.check("7"));
+}
+
+#[test]
+fn property_single_letter() {
+    assert_eq!(find_all("\\pN+", "ab12cd"), vec!["12"]);
+    assert_eq!(find_all("\\PL+", "ab12cd"), vec!["12"]);
+}
+
+#[test]
+fn property_script() {
+    assert_eq!(find_all("\\p{Greek}+", "abc αβγ def"), vec!["αβγ"]);
+    assert_eq!(find_all("\\p{Han}+", "in 漢字 text"), vec!["漢字"]);
+    assert_eq!(find_all("\\p{Cyrl}+", "мир peace"), vec!["мир"]);
+}
+
+#[test]
+fn property_negated() {
+    assert_eq!(find_all("\\P{Greek}+", "αβ ab γ"), vec![" ab "]);
+    assert!(!Regex::new("\\P{L}").unwrap().check("é"));
+}
+
+#[test]
+fn property_in_class() {
+    assert_eq!(find_all("[\\p{Greek}\\d]+", "αβ12 ab"), vec!["αβ12"]);
+    assert_eq!(find_all("[^\\p{L}\\s]+", "ab, cd!"), vec![",", "!"]);
+    assert_eq!(find_all("[\\P{L}a]+", "ab1 c"), vec!["a", "1 "]);
+}
+
+#[test]
+fn property_case_insensitive() {
+    let r = Regex::new("(?i)\\p{Lu}+").unwrap();
+    assert!(r.check("AbC"));
+}
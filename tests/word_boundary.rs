@@ -0,0 +1,75 @@
+use regex_engine::Regex;
+
+fn find_all(pattern: &str, text: &str) -> Vec<String> {
+    let r = Regex::new(pattern).unwrap();
+    r.find_iter(text).map(|m| m.as_str().to_string()).collect()
+}
+
+#[test]
+fn word_boundary_keyword() {
+    let r = Regex::new("\\bfor\\b").unwrap();
+    assert!(r.has_match("for x in y"));
+    assert!(r.has_match("x = (for)"));
+    assert!(!r.has_match("before"));
+    assert!(!r.has_match("format"));
+}
+
+#[test]
+fn word_boundary_words() {
+    let res = find_all("\\b[a-z]+\\b", "one two3 four, five");
+    assert_eq!(res, vec!["one", "four", "five"]);
+}
+
+#[test]
+fn word_boundary_not() {
+    let res = find_all("\\Bo\\B", "foo too o bob");
+    assert_eq!(res, vec!["o", "o", "o"]);
+}
+
+#[test]
+fn word_boundary_empty() {
+    let r = Regex::new("\\b").unwrap();
+    let starts: Vec<_> = r.find_iter("ab cd").map(|m| m.start()).collect();
+    assert_eq!(starts, vec![0, 2, 3, 5]);
+
+    assert!(!r.has_match(""));
+    assert!(!r.has_match(" - "));
+}
+
+#[test]
+fn word_boundary_unicode() {
+    let res = find_all("\\b[a-zé]+\\b", "café cafés naïve");
+    assert_eq!(res, vec!["café", "cafés"]);
+
+    let r = Regex::new("\\bδ\\b").unwrap();
+    assert!(r.has_match("α δ β"));
+    assert!(!r.has_match("αδβ"));
+}
+
+#[test]
+fn word_boundary_ascii() {
+    // Without Unicode, `é` isn't a word char
+    let res = find_all("(?-u)\\b[a-z]+\\b", "café naïve");
+    assert_eq!(res, vec!["caf", "na", "ve"]);
+
+    let res = find_all("(?-u:\\B)a", "éa ba");
+    assert_eq!(res, vec!["a"]);
+}
+
+#[test]
+fn word_boundary_search_from() {
+    // The char before a later match is still looked at
+    let res = find_all("\\bb", "bb b");
+    assert_eq!(res, vec!["b", "b"]);
+
+    let r = Regex::new("\\bb").unwrap();
+    let starts: Vec<_> = r.find_iter("bb b").map(|m| m.start()).collect();
+    assert_eq!(starts, vec![0, 3]);
+}
+
+#[test]
+fn word_boundary_captures() {
+    let r = Regex::new("\\b([a-z]+)\\b$").unwrap();
+    let caps = r.captures("ab cd").unwrap();
+    assert_eq!(caps.get(1).map(|m| m.as_str()), Some("cd"));
+}